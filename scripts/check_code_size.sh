@@ -0,0 +1,22 @@
+#!/bin/sh
+# Build examples/minimal_reads and check that none of the NVM code ends up in
+# the binary, backing the code size note in the README.
+#
+# The debug profile is used so that nothing is inlined: every driver function
+# that is linked in keeps its own symbol. Debug formatting of Error and
+# RegisterNvm is expected, since the example prints errors.
+set -eu
+cd "$(dirname "$0")/.."
+
+cargo build --example minimal_reads
+symbols=$(nm -C target/debug/examples/minimal_reads | grep 'max17320' | grep -v 'core::fmt::Debug>::fmt')
+
+if ! echo "$symbols" | grep -q 'read_vcell'; then
+    echo "read_vcell not found, the symbol check is not working" >&2
+    exit 1
+fi
+if echo "$symbols" | grep -Ei 'nvm|nonvolatile'; then
+    echo "NVM code is linked into minimal_reads" >&2
+    exit 1
+fi
+echo "no NVM code in minimal_reads"
@@ -0,0 +1,113 @@
+//! Async driver for the operations that keep the chip busy for milliseconds to
+//! seconds (NVM commit, model refresh). These await the delay between polls so
+//! the executor can run other tasks, using the same sequences as the blocking
+//! driver.
+
+use crate::error::Error;
+use crate::i2c_interface::write_frame;
+use crate::register::*;
+use crate::sequence::{Action, ModelRefreshSequence, NvmSequence};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+/// Async MAX17320 driver
+#[derive(Debug)]
+pub struct MAX17320Async<I2C, D> {
+    com: I2C,
+    delay: D,
+    address: u8,
+    address_nvm: u8,
+}
+
+impl<I2C, D> MAX17320Async<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create new driver interface at the default addresses
+    pub fn new(i2c: I2C, delay: D) -> Self {
+        Self::with_addresses(i2c, delay, 0x36, 0x0B)
+    }
+
+    /// Create new driver interface with specific I2C addresses
+    pub fn with_addresses(i2c: I2C, delay: D, address: u8, address_nvm: u8) -> Self {
+        Self {
+            com: i2c,
+            delay,
+            address,
+            address_nvm,
+        }
+    }
+
+    /// Destroy the driver and return the I2C bus and delay
+    pub fn release(self) -> (I2C, D) {
+        (self.com, self.delay)
+    }
+
+    /// Copy the nonvolatile shadow RAM into NVM, consuming one of the limited
+    /// NVM write cycles. Yields while the chip programs the NVM (up to tBLOCK).
+    pub async fn commit_nonvolatile_memory(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.unlock_write_protection().await?;
+        let mut sequence = NvmSequence::command(CommandCode::CopyNvBlock, None);
+        self.run_sequence(|read| sequence.step(read)).await?;
+        self.lock_write_protection().await
+    }
+
+    /// Set ModelCfg.Refresh so the gauge reprocesses model related configuration,
+    /// and wait for it to finish.
+    pub async fn refresh_model(&mut self) -> Result<(), Error<I2C::Error>> {
+        let code = self.read_register(Register::ModelCfg).await?;
+        self.unlock_write_protection().await?;
+        self.write_register(Register::ModelCfg, code | ModelCfgCode::Refresh as u16)
+            .await?;
+        self.lock_write_protection().await?;
+        let mut sequence = ModelRefreshSequence::new();
+        self.run_sequence(|read| sequence.step(read)).await
+    }
+
+    /// Same as the blocking driver: the CHGOff/DISOff FET overrides are preserved
+    async fn unlock_write_protection(&mut self) -> Result<(), Error<I2C::Error>> {
+        let fet_bits = self.read_register(Register::CommStat).await? & FET_OVERRIDE_BITS;
+        self.write_register(Register::CommStat, fet_bits).await?;
+        self.write_register(Register::CommStat, fet_bits).await?;
+        Ok(())
+    }
+
+    async fn lock_write_protection(&mut self) -> Result<(), Error<I2C::Error>> {
+        let fet_bits = self.read_register(Register::CommStat).await? & FET_OVERRIDE_BITS;
+        self.write_register(Register::CommStat, fet_bits | 0x00F9)
+            .await?;
+        self.write_register(Register::CommStat, fet_bits | 0x00F9)
+            .await?;
+        Ok(())
+    }
+
+    async fn run_sequence<F>(&mut self, mut step: F) -> Result<(), Error<I2C::Error>>
+    where
+        F: FnMut(u16) -> Result<Action, Error<I2C::Error>>,
+    {
+        let mut read = 0;
+        loop {
+            match step(read)? {
+                Action::Write(reg, code) => self.write_register(reg, code).await?,
+                Action::Read(reg) => read = self.read_register(reg).await?,
+                Action::Delay(us) => self.delay.delay_us(us).await,
+                Action::Done => return Ok(()),
+            }
+        }
+    }
+
+    async fn read_register(&mut self, reg: Register) -> Result<u16, I2C::Error> {
+        let mut data = [0; 2];
+        self.com
+            .write_read(self.address, &[reg as u8], &mut data)
+            .await?;
+        Ok(u16::from_le_bytes(data))
+    }
+
+    async fn write_register(&mut self, reg: Register, code: u16) -> Result<(), I2C::Error> {
+        self.com
+            .write(self.address, &write_frame(reg as u8, code))
+            .await
+    }
+}
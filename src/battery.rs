@@ -0,0 +1,51 @@
+use super::*;
+
+/// Fuel gauge agnostic battery readings, so power management code can be
+/// generic over the gauge chip
+pub trait Battery {
+    /// Error returned by the underlying gauge
+    type Error;
+
+    /// State of charge (%)
+    fn state_of_charge(&mut self) -> Result<f32, Self::Error>;
+
+    /// Total pack voltage (V)
+    fn voltage(&mut self) -> Result<f32, Self::Error>;
+
+    /// Pack current (mA), positive when charging
+    fn current(&mut self) -> Result<f32, Self::Error>;
+
+    /// Pack temperature (°C)
+    fn temperature(&mut self) -> Result<f32, Self::Error>;
+
+    /// Estimated time until empty (seconds)
+    fn time_to_empty(&mut self) -> Result<f32, Self::Error>;
+}
+
+impl<I2C, D, E> Battery for MAX17320<I2C, D>
+where
+    I2C: WriteRead<Error = E> + Write<Error = E> + Read<Error = E>,
+    D: DelayUs<u32>,
+{
+    type Error = Error<E>;
+
+    fn state_of_charge(&mut self) -> Result<f32, Self::Error> {
+        self.read_state_of_charge()
+    }
+
+    fn voltage(&mut self) -> Result<f32, Self::Error> {
+        self.read_batt()
+    }
+
+    fn current(&mut self) -> Result<f32, Self::Error> {
+        self.read_current()
+    }
+
+    fn temperature(&mut self) -> Result<f32, Self::Error> {
+        self.read_temperature()
+    }
+
+    fn time_to_empty(&mut self) -> Result<f32, Self::Error> {
+        self.read_time_to_empty()
+    }
+}
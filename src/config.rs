@@ -39,3 +39,531 @@ pub enum BatteryPackUpdate {
     /// Pckp/Batt channels update after all cell measurements are completed
     AfterMeasurementsCompleted = 1 << 13,
 }
+
+/// Per-page write protection settings for CommStat.WP1-WP5.
+/// A field set to true keeps the listed register pages write protected.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WriteProtectionPages {
+    /// WP1: register pages 1Ah, 1Bh, 1Eh
+    pub wp1: bool,
+    /// WP2: register pages 01h, 02h, 03h, 04h, 0Bh, 0Dh
+    pub wp2: bool,
+    /// WP3: register pages 18h, 19h (nonvolatile shadow RAM)
+    pub wp3: bool,
+    /// WP4: register page 1Ch
+    pub wp4: bool,
+    /// WP5: register page 1Dh
+    pub wp5: bool,
+}
+
+impl WriteProtectionPages {
+    /// Every page protected
+    pub const ALL: Self = Self {
+        wp1: true,
+        wp2: true,
+        wp3: true,
+        wp4: true,
+        wp5: true,
+    };
+
+    /// Every page writable
+    pub const NONE: Self = Self {
+        wp1: false,
+        wp2: false,
+        wp3: false,
+        wp4: false,
+        wp5: false,
+    };
+
+    /// Decode the write protection bits of CommStat
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            wp1: bits & (1 << 3) != 0,
+            wp2: bits & (1 << 4) != 0,
+            wp3: bits & (1 << 5) != 0,
+            wp4: bits & (1 << 6) != 0,
+            wp5: bits & (1 << 7) != 0,
+        }
+    }
+
+    /// Encode as CommStat write protection bits. The global write protection
+    /// bit is only set when every page is protected, since it overrides WP1-WP5.
+    pub fn to_bits(self) -> u16 {
+        let bits = (self.wp1 as u16) << 3
+            | (self.wp2 as u16) << 4
+            | (self.wp3 as u16) << 5
+            | (self.wp4 as u16) << 6
+            | (self.wp5 as u16) << 7;
+        if self == Self::ALL {
+            bits | 1
+        } else {
+            bits
+        }
+    }
+}
+
+/// Cell chemistry model used by ModelGauge m5 (ModelCfg.ModelID)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CellModel {
+    /// Standard lithium cobalt oxide and most other Li-ion cells (default)
+    LithiumCobalt = 0,
+    /// Lithium NCR or NCA cells
+    LithiumNca = 2 << 4,
+    /// Lithium iron phosphate (LiFePO4) cells
+    LithiumIronPhosphate = 6 << 4,
+}
+
+/// Charge voltage class of the cells (ModelCfg.VChg)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChargeVoltageClass {
+    /// Cells charged to 4.275V or below
+    UpTo4p275V = 0,
+    /// Cells charged above 4.275V
+    Above4p275V = 1 << 10,
+}
+
+/// Hibernate mode configuration in physical units (HibCfg)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HibernateConfig {
+    /// Hibernate mode is allowed
+    pub enabled: bool,
+    /// The gauge enters hibernate once |current| stays below this (mA)
+    pub entry_current_ma: f32,
+    /// How long the current must stay below the threshold before entering hibernate
+    pub entry_time: core::time::Duration,
+}
+
+/// Constant voltage taper model used by the time to full estimate (nTTFCfg)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeToFullConfig {
+    /// State of charge at which the charger enters the CV phase (%), 0 to 127.5 in 0.5% steps
+    pub cv_start_percent: f32,
+    /// Time for the charge current to halve during the CV phase, 0 to 1434s in 5.625s steps
+    pub cv_half_time: core::time::Duration,
+}
+
+impl TimeToFullConfig {
+    const CV_START_LSB: f32 = 0.5;
+    const CV_HALF_TIME_LSB_MS: u64 = 5625;
+
+    /// Decode the nTTFCfg register
+    pub fn from_bits(bits: u16) -> Self {
+        let [start, half_time] = bits.to_be_bytes();
+        Self {
+            cv_start_percent: start as f32 * Self::CV_START_LSB,
+            cv_half_time: core::time::Duration::from_millis(
+                half_time as u64 * Self::CV_HALF_TIME_LSB_MS,
+            ),
+        }
+    }
+
+    /// Encode as the nTTFCfg register, or None if a field is out of range
+    pub fn to_bits(self) -> Option<u16> {
+        self.encode_checked().ok()
+    }
+
+    /// Like to_bits, but the error is the out of range field: cv_start_percent,
+    /// or cv_half_time in seconds
+    pub(crate) fn encode_checked(self) -> Result<u16, u16> {
+        let start = self.cv_start_percent / Self::CV_START_LSB;
+        if !(0.0..=255.0).contains(&start) {
+            return Err(self.cv_start_percent as u16);
+        }
+        let half_time = self.cv_half_time.as_millis() as u64 / Self::CV_HALF_TIME_LSB_MS;
+        if half_time > 255 {
+            return Err(self.cv_half_time.as_secs() as u16);
+        }
+        Ok(u16::from_be_bytes([start as u8, half_time as u8]))
+    }
+}
+
+/// Thermistor curve coefficients (nTGain, nTOff, nTCurve) used to convert the
+/// thermistor ratio to temperature. Compute these from the NTC's datasheet
+/// curve to support thermistors other than the default.
+///
+/// There are no thermistor bias-enable or curve-select bits to go with these:
+/// the MAX17320 has no nThermCfg register. The thermistor channels and their
+/// 10k/100k NTC type are selected in nPackCfg with set_pack_config, and there
+/// is no separate bias-enable bit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThermistorConfig {
+    /// Thermistor gain (nTGain)
+    pub gain: u16,
+    /// Thermistor offset (nTOff)
+    pub offset: u16,
+    /// Curve correction applied at the temperature extremes (nTCurve)
+    pub curve: u16,
+}
+
+impl ThermistorConfig {
+    /// Coefficients for a 10kΩ, beta 3435 NTC such as the Murata NCP15XH103F03RC
+    pub const NTC_10K_BETA_3435: Self = Self {
+        gain: 0xEE56,
+        offset: 0x1DA4,
+        curve: 0x0025,
+    };
+}
+
+/// nNVCfg0-2, which control which registers are restored from NVM and which
+/// learned values the gauge writes back to NVM on its own
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NvConfig {
+    /// nNVCfg0: registers restored from NVM at reset
+    pub nv_cfg0: u16,
+    /// nNVCfg1: additional registers restored from NVM at reset
+    pub nv_cfg1: u16,
+    /// nNVCfg2: learned value write-back enables and save interval
+    pub nv_cfg2: u16,
+}
+
+impl NvConfig {
+    const CYCLES_NV_CFG_MASK: u16 = 0x001F;
+    const SAVE_ENABLE_MASK: u16 = 0xFC00;
+
+    /// Number of Cycles LSBs (16% of a full cycle each) between automatic NVM
+    /// saves of learned values. 0 means the gauge never saves on its own.
+    pub fn auto_save_interval(&self) -> u8 {
+        (self.nv_cfg2 & Self::CYCLES_NV_CFG_MASK) as u8
+    }
+
+    /// Set the automatic save interval, 0 to 31. 0 disables automatic saving.
+    pub fn set_auto_save_interval(&mut self, interval: u8) {
+        self.nv_cfg2 = (self.nv_cfg2 & !Self::CYCLES_NV_CFG_MASK)
+            | (interval as u16 & Self::CYCLES_NV_CFG_MASK);
+    }
+
+    /// Which learned values are written back on each automatic save, using
+    /// the nNVCfg2 bit layout (enVT, enMMC, enMMV, enMMT, enSOC, enT in bits 15-10)
+    pub fn auto_save_enables(&self) -> u16 {
+        self.nv_cfg2 & Self::SAVE_ENABLE_MASK
+    }
+
+    /// Stop the gauge from writing to NVM on its own, preserving the write
+    /// budget for explicit commits
+    pub fn disable_auto_save(&mut self) {
+        self.nv_cfg2 &= !(Self::CYCLES_NV_CFG_MASK | Self::SAVE_ENABLE_MASK);
+    }
+}
+
+/// Optional protection features enabled in nProtCfg2
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProtectionConfig2 {
+    /// Internal self-discharge (leakage) detection
+    pub leakage_detection: bool,
+    /// Multicell imbalance protection
+    pub imbalance_protection: bool,
+    /// Prequal (precharge) timeout protection
+    pub prequal_timeout_protection: bool,
+    /// Capacity overflow protection
+    pub capacity_overflow_protection: bool,
+    /// Die overtemperature protection
+    pub die_overtemperature_protection: bool,
+}
+
+impl ProtectionConfig2 {
+    /// Bits of nProtCfg2 covered by this struct; the rest are left untouched on write
+    pub const MASK: u16 = 0x001F;
+
+    /// Decode the nProtCfg2 register
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            leakage_detection: bits & 1 != 0,
+            imbalance_protection: bits & (1 << 1) != 0,
+            prequal_timeout_protection: bits & (1 << 2) != 0,
+            capacity_overflow_protection: bits & (1 << 3) != 0,
+            die_overtemperature_protection: bits & (1 << 4) != 0,
+        }
+    }
+
+    /// Encode as nProtCfg2 register bits
+    pub fn to_bits(self) -> u16 {
+        self.leakage_detection as u16
+            | (self.imbalance_protection as u16) << 1
+            | (self.prequal_timeout_protection as u16) << 2
+            | (self.capacity_overflow_protection as u16) << 3
+            | (self.die_overtemperature_protection as u16) << 4
+    }
+}
+
+/// Averaging time constants applied by the gauge (nFilterCfg)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FilterConfig {
+    /// AvgCurrent time constant, 0.35s to 3.2h
+    pub current: core::time::Duration,
+    /// AvgVCell time constant, 11.25s to 24min
+    pub voltage: core::time::Duration,
+    /// AvgTA time constant, 45s to 96min
+    pub temperature: core::time::Duration,
+}
+
+impl FilterConfig {
+    /// Bits of nFilterCfg covered by this struct; the rest are left untouched on write
+    pub const MASK: u16 = 0x387F;
+    /// Every field is 45s * 2^(code - offset)
+    const BASE_MS: u64 = 45_000;
+    const CURRENT_OFFSET: u8 = 7;
+    const VOLTAGE_OFFSET: u8 = 2;
+
+    fn decode(code: u16, offset: u8) -> core::time::Duration {
+        let code = code as u8;
+        let us = Self::BASE_MS * 1000;
+        core::time::Duration::from_micros(if code >= offset {
+            us << (code - offset)
+        } else {
+            us >> (offset - code)
+        })
+    }
+
+    /// Nearest code for tc, or None if tc is more than half a step outside the field's range
+    fn encode(tc: core::time::Duration, offset: u8, max: u8) -> Option<u16> {
+        let tc = tc.as_micros() as f32;
+        (0..=max)
+            .find(|&code| {
+                // geometric midpoint between this step and the next
+                Self::decode(code as u16, offset).as_micros() as f32 * core::f32::consts::SQRT_2
+                    >= tc
+            })
+            .filter(|&code| {
+                Self::decode(code as u16, offset).as_micros() as f32 / core::f32::consts::SQRT_2
+                    <= tc
+            })
+            .map(u16::from)
+    }
+
+    /// Decode the nFilterCfg register
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            current: Self::decode(bits & 0x0F, Self::CURRENT_OFFSET),
+            voltage: Self::decode((bits >> 4) & 0x07, Self::VOLTAGE_OFFSET),
+            temperature: Self::decode((bits >> 11) & 0x07, 0),
+        }
+    }
+
+    /// Encode as nFilterCfg register bits, rounding each time constant to the
+    /// nearest power of two step, or None if a field is out of range
+    pub fn to_bits(self) -> Option<u16> {
+        self.encode_checked().ok()
+    }
+
+    /// Like to_bits, but the error is the out of range time constant (s)
+    pub(crate) fn encode_checked(self) -> Result<u16, u16> {
+        let field = |tc: core::time::Duration, offset, max| {
+            Self::encode(tc, offset, max).ok_or(tc.as_secs() as u16)
+        };
+        let current = field(self.current, Self::CURRENT_OFFSET, 15)?;
+        let voltage = field(self.voltage, Self::VOLTAGE_OFFSET, 7)?;
+        let temperature = field(self.temperature, 0, 7)?;
+        Ok(current | voltage << 4 | temperature << 11)
+    }
+}
+
+/// Which gauge alert conditions assert the ALRT pin, see read_alrt_pin_sources.
+/// A source is false if Config.Aen is clear, if its thresholds are at the
+/// disabled extremes, or for temperature if Config2.TAlrtEn is clear. Such
+/// conditions may still set their Status bits.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlrtPinSources {
+    /// Threshold violations drive the ALRT pin at all (Config.Aen)
+    pub pin_enabled: bool,
+    /// VAlrtTh violations (Status.Vmn/Vmx)
+    pub voltage: bool,
+    /// TAlrtTh violations (Status.Tmn/Tmx)
+    pub temperature: bool,
+    /// SAlrtTh violations (Status.Smn/Smx)
+    pub soc: bool,
+    /// IAlrtTh violations (Status.Imn/Imx)
+    pub current: bool,
+    /// 1% SOC changes (Status.dSOCi, Config2.dSOCen)
+    pub soc_change: bool,
+}
+
+/// How long each protection condition must persist before the protector acts
+/// (nDelayCfg). Every field is a 2 bit code selecting base * 2^code.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProtectionTimers {
+    /// Undervoltage trip delay (UVPTimer, bits 1:0)
+    pub undervoltage: core::time::Duration,
+    /// Overvoltage trip delay (OVPTimer, bits 3:2)
+    pub overvoltage: core::time::Duration,
+    /// Permanent failure confirmation delay (PermFailTimer, bits 5:4)
+    pub permanent_fail: core::time::Duration,
+    /// Over/undertemperature trip delay (TempTimer, bits 7:6)
+    pub temperature: core::time::Duration,
+    /// Overcharge current trip delay (OCCTimer, bits 9:8)
+    pub overcharge_current: core::time::Duration,
+    /// Overdischarge current trip delay (OCDTimer, bits 11:10)
+    pub overdischarge_current: core::time::Duration,
+    /// Full detection debounce (FullTimer, bits 13:12)
+    pub full_detection: core::time::Duration,
+    /// Charge watchdog timeout (CHGWDT, bits 15:14)
+    pub charge_watchdog: core::time::Duration,
+}
+
+impl ProtectionTimers {
+    const VOLTAGE_BASE_US: u64 = 117_200;
+    const PERM_FAIL_BASE_US: u64 = 1_406_000;
+    const TEMP_BASE_US: u64 = 1_406_000;
+    const CURRENT_BASE_US: u64 = 70_300;
+    const FULL_BASE_US: u64 = 351_600;
+    const CHARGE_WATCHDOG_BASE_US: u64 = 15 * 60 * 1_000_000;
+
+    fn field(bits: u16, shift: u8, base_us: u64) -> core::time::Duration {
+        core::time::Duration::from_micros(base_us << ((bits >> shift) & 0x3))
+    }
+
+    /// nDelayCfg.CHGWDT, bits 15:14
+    pub(crate) const CHARGE_WATCHDOG_MASK: u16 = 0xC000;
+
+    /// Encode a charge watchdog timeout (15, 30, 60 or 120 minutes exactly) as
+    /// nDelayCfg bits, or None if the chip cannot time it
+    pub(crate) fn charge_watchdog_bits(timeout: core::time::Duration) -> Option<u16> {
+        (0..4)
+            .find(|code| Self::field(code << 14, 14, Self::CHARGE_WATCHDOG_BASE_US) == timeout)
+            .map(|code| code << 14)
+    }
+
+    /// Decode the nDelayCfg register
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            undervoltage: Self::field(bits, 0, Self::VOLTAGE_BASE_US),
+            overvoltage: Self::field(bits, 2, Self::VOLTAGE_BASE_US),
+            permanent_fail: Self::field(bits, 4, Self::PERM_FAIL_BASE_US),
+            temperature: Self::field(bits, 6, Self::TEMP_BASE_US),
+            overcharge_current: Self::field(bits, 8, Self::CURRENT_BASE_US),
+            overdischarge_current: Self::field(bits, 10, Self::CURRENT_BASE_US),
+            full_detection: Self::field(bits, 12, Self::FULL_BASE_US),
+            charge_watchdog: Self::field(bits, 14, Self::CHARGE_WATCHDOG_BASE_US),
+        }
+    }
+}
+
+/// Which voltage the overvoltage/undervoltage protection compares against its thresholds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProtectionVoltageMode {
+    /// Each cell is compared individually, so one weak cell trips protection
+    PerCell,
+    /// The pack total is compared
+    PackTotal,
+}
+
+/// What a thermistor channel (TH1-TH4) is used for. The MAX17320 has no
+/// monitor-only mode: every measured channel feeds the temperature protection.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ThermChannelRole {
+    /// Not measured
+    Disabled,
+    /// Measured and used by the temperature protection
+    Protection,
+}
+
+/// All four alert threshold pairs, see set_all_alert_thresholds. Each pair is (min, max).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlertThresholds {
+    /// Cell voltage (V), 0.0 to 5.1 in 0.02V steps
+    pub voltage_v: (f32, f32),
+    /// Temperature (°C)
+    pub temperature_c: (i8, i8),
+    /// State of charge (%)
+    pub soc_percent: (u8, u8),
+    /// Current in units of 400µV across the sense resistor
+    pub current: (i8, i8),
+}
+
+/// Field of AlertThresholds that failed validation
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlertThresholdField {
+    /// AlertThresholds::voltage_v
+    Voltage,
+    /// AlertThresholds::temperature_c
+    Temperature,
+    /// AlertThresholds::soc_percent
+    Soc,
+    /// AlertThresholds::current
+    Current,
+}
+
+impl AlertThresholds {
+    /// Check every pair has min <= max, returning the first field that does not.
+    /// The voltage step size is checked by set_all_alert_thresholds.
+    pub fn validate(&self) -> Result<(), AlertThresholdField> {
+        if self.voltage_v.0 > self.voltage_v.1 {
+            return Err(AlertThresholdField::Voltage);
+        }
+        if self.temperature_c.0 > self.temperature_c.1 {
+            return Err(AlertThresholdField::Temperature);
+        }
+        if self.soc_percent.0 > self.soc_percent.1 {
+            return Err(AlertThresholdField::Soc);
+        }
+        if self.current.0 > self.current.1 {
+            return Err(AlertThresholdField::Current);
+        }
+        Ok(())
+    }
+}
+
+/// Protection FET drive, see read_fet_configuration
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FetConfiguration {
+    /// Charge pump gate drive voltage (nPackCfg.CPCfg)
+    pub charge_pump: ChargePumpVoltageConfiguration,
+}
+
+impl FetConfiguration {
+    /// Bits of nPackCfg covered by this struct
+    pub const MASK: u16 = 0b11 << 8;
+
+    /// Decode from nPackCfg, or None for the reserved CPCfg code
+    pub fn from_bits(bits: u16) -> Option<Self> {
+        let charge_pump = match bits & Self::MASK {
+            0 => ChargePumpVoltageConfiguration::Cp6V,
+            x if x == ChargePumpVoltageConfiguration::Cp8V as u16 => {
+                ChargePumpVoltageConfiguration::Cp8V
+            }
+            x if x == ChargePumpVoltageConfiguration::Cp10V as u16 => {
+                ChargePumpVoltageConfiguration::Cp10V
+            }
+            _ => return None,
+        };
+        Some(Self { charge_pump })
+    }
+}
+
+/// What the gauge writes back to NVM on its own, decoded from nNVCfg2. Every
+/// automatic save uses one of the limited NVM write cycles.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AutoSaveConfig {
+    /// Automatic saving happens at all: a nonzero interval with at least one value enabled
+    pub enabled: bool,
+    /// Cycles LSBs (16% of a full cycle each) between saves, 0 disables saving
+    pub interval: u8,
+    /// Voltage and temperature learned values (enVT)
+    pub voltage_temperature: bool,
+    /// Max/min current log (enMMC)
+    pub max_min_current: bool,
+    /// Max/min voltage log (enMMV)
+    pub max_min_voltage: bool,
+    /// Max/min temperature log (enMMT)
+    pub max_min_temperature: bool,
+    /// State of charge (enSOC)
+    pub soc: bool,
+    /// Operating time (enT)
+    pub time: bool,
+}
+
+impl From<NvConfig> for AutoSaveConfig {
+    fn from(config: NvConfig) -> Self {
+        let enables = config.auto_save_enables();
+        let interval = config.auto_save_interval();
+        Self {
+            enabled: interval != 0 && enables != 0,
+            interval,
+            voltage_temperature: enables & (1 << 15) != 0,
+            max_min_current: enables & (1 << 14) != 0,
+            max_min_voltage: enables & (1 << 13) != 0,
+            max_min_temperature: enables & (1 << 12) != 0,
+            soc: enables & (1 << 11) != 0,
+            time: enables & (1 << 10) != 0,
+        }
+    }
+}
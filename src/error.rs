@@ -1,6 +1,8 @@
+use crate::config::AlertThresholdField;
 use crate::register;
 
 /// MPU Error
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Error<E> {
     /// WHO_AM_I returned invalid value (returned value is argument).
@@ -13,6 +15,38 @@ pub enum Error<E> {
     NonvolatileError(register::RegisterNvm),
     /// Invalid configuration value.
     InvalidConfigurationValue(u16),
+    /// A protection FET did not reach the commanded state.
+    FetDidNotSwitch,
+    /// A register that cannot be all zeros or all ones read as such, which
+    /// usually means the wrong address, missing pull-ups or a stuck bus.
+    NoResponse,
+    /// An NVM register did not read back the value written (9-bit address is argument).
+    NvmVerifyFailed(u16),
+    /// No NVM write cycles are left.
+    NvmWritesExhausted,
+    /// CommStat.NVError was set after copying the shadow RAM to NVM
+    /// (CopyNvBlock), so the NVM contents may not match the shadow RAM.
+    NvmCopyFailed,
+    /// Bus error while addressing the NVM page (0x0B by default), e.g. because
+    /// it is not wired or strapped differently. The main address is not probed,
+    /// so this does not prove the main address is reachable (bus error is
+    /// argument).
+    NvmAddressUnreachable(E),
+    /// A register did not read back the value written (register address is argument).
+    WriteVerifyFailed(u8),
+    /// An alert threshold pair is out of range or has min above max (field is argument).
+    InvalidAlertThreshold(AlertThresholdField),
+    /// The MAX17320 only supports 2S-4S packs (requested cell count is argument).
+    UnsupportedCellCount(u8),
+    /// A nonvolatile write was attempted while CommStat write protection is set,
+    /// so the chip would have silently ignored it.
+    WriteProtected,
+    /// A load current was not finite and positive, or was too small for the
+    /// projected runtime to fit a Duration.
+    InvalidLoadCurrent,
+    /// The MAX17320 only protects per cell, so PackTotal protection cannot be
+    /// selected.
+    UnsupportedProtectionVoltageMode,
 }
 
 impl<E> From<E> for Error<E> {
@@ -20,3 +54,39 @@ impl<E> From<E> for Error<E> {
         Error::BusError(error)
     }
 }
+
+impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidDevice(id) => write!(f, "invalid device id {:#04x}", id),
+            Error::BusError(e) => write!(f, "i2c bus error: {:?}", e),
+            Error::Timeout => write!(f, "timed out"),
+            Error::NonvolatileError(reg) => write!(f, "nonvolatile memory error at {:?}", reg),
+            Error::InvalidConfigurationValue(v) => write!(f, "invalid configuration value {}", v),
+            Error::FetDidNotSwitch => write!(f, "protection FET did not switch"),
+            Error::NoResponse => write!(f, "no response from device"),
+            Error::NvmVerifyFailed(addr) => write!(f, "NVM readback mismatch at {:#05x}", addr),
+            Error::NvmWritesExhausted => write!(f, "no NVM write cycles remaining"),
+            Error::NvmCopyFailed => write!(f, "NVM block copy failed"),
+            Error::NvmAddressUnreachable(e) => write!(f, "bus error at NVM address: {:?}", e),
+            Error::WriteVerifyFailed(reg) => write!(f, "readback mismatch at {:#04x}", reg),
+            Error::InvalidAlertThreshold(field) => write!(f, "invalid {:?} alert threshold", field),
+            Error::UnsupportedCellCount(n) => write!(f, "unsupported cell count {}S", n),
+            Error::WriteProtected => write!(f, "write protection is enabled"),
+            Error::InvalidLoadCurrent => write!(f, "invalid load current"),
+            Error::UnsupportedProtectionVoltageMode => {
+                write!(f, "only per cell voltage protection is supported")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::BusError(e) | Error::NvmAddressUnreachable(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,27 +1,102 @@
 use super::*;
-use crate::register::Register;
+use crate::register::{Register, RegisterSecondary};
+use crate::sequence::{Action, NvmSequence};
 
-const MAX_LOOP: u16 = 500;
+/// Largest number of registers read in one block transaction
+pub(crate) const MAX_BLOCK_WORDS: usize = 16;
 
-impl<I2C, E> MAX17320<I2C>
+/// Bytes of a register write: the register address followed by the value,
+/// least significant byte first like register reads
+pub(crate) fn write_frame(reg: u8, code: u16) -> [u8; 3] {
+    let [low, high] = code.to_le_bytes();
+    [reg, low, high]
+}
+
+impl<I2C, D, E> MAX17320<I2C, D>
 where
     I2C: WriteRead<Error = E> + Write<Error = E> + Read<Error = E>,
+    D: DelayUs<u32>,
 {
-    pub(crate) fn read_named_register(&mut self, reg: Register) -> Result<u16, E> {
+    pub(crate) fn read_named_register(&mut self, reg: Register) -> Result<u16, Error<E>> {
         self.read_register(reg as u8, self.address)
     }
 
-    pub(crate) fn read_named_register_nvm(&mut self, reg: RegisterNvm) -> Result<u16, E> {
+    /// Read a register that can never legitimately be 0x0000 or 0xFFFF, returning
+    /// Error::NoResponse if it is
+    pub(crate) fn read_named_register_checked(&mut self, reg: Register) -> Result<u16, Error<E>> {
+        let val = self.read_named_register(reg)?;
+        if looks_like_no_device(val) {
+            return Err(Error::NoResponse);
+        }
+        Ok(val)
+    }
+
+    /// Read a register holding a two's complement value (Current, Temp, DieTemp, ...)
+    pub(crate) fn read_named_register_signed(&mut self, reg: Register) -> Result<i16, Error<E>> {
+        Ok(self.read_named_register(reg)? as i16)
+    }
+
+    pub(crate) fn read_named_register_nvm(&mut self, reg: RegisterNvm) -> Result<u16, Error<E>> {
         self.read_register(reg as u8, self.address_nvm)
     }
 
-    fn read_register(&mut self, reg: u8, address: u8) -> Result<u16, E> {
+    /// Read a nonvolatile register holding a two's complement value
+    pub(crate) fn read_named_register_nvm_signed(
+        &mut self,
+        reg: RegisterNvm,
+    ) -> Result<i16, Error<E>> {
+        Ok(self.read_named_register_nvm(reg)? as i16)
+    }
+
+    /// Read a two's complement volatile register on the secondary address
+    pub(crate) fn read_named_register_secondary_signed(
+        &mut self,
+        reg: RegisterSecondary,
+    ) -> Result<i16, Error<E>> {
+        Ok(self.read_register(reg as u8, self.address_nvm)? as i16)
+    }
+
+    /// Read up to MAX_BLOCK_WORDS consecutive registers in a single transaction.
+    ///
+    /// The data is staged in a local buffer and only copied to out once the whole
+    /// transfer has succeeded, so out is never left holding a mix of new and stale
+    /// words. The blocking I2C traits report a short transfer as a bus error, which
+    /// is returned as Error::BusError (Error::NvmAddressUnreachable at the NVM address).
+    pub(super) fn read_block(
+        &mut self,
+        reg: u8,
+        address: u8,
+        out: &mut [u16],
+    ) -> Result<(), Error<E>> {
+        if out.len() > MAX_BLOCK_WORDS {
+            return Err(Error::InvalidConfigurationValue(out.len() as u16));
+        }
+        let mut data = [0u8; MAX_BLOCK_WORDS * 2];
+        let data = &mut data[..out.len() * 2];
+        self.count_transaction();
+        self.com
+            .write_read(address, &[reg], data)
+            .map_err(|e| self.bus_error(address, e))?;
+        for (word, bytes) in out.iter_mut().zip(data.chunks_exact(2)) {
+            *word = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(())
+    }
+
+    fn read_register(&mut self, reg: u8, address: u8) -> Result<u16, Error<E>> {
         let mut data: [u8; 2] = [0, 0];
-        self.com.write_read(address, &[reg], &mut data)?;
+        self.count_transaction();
+        self.com
+            .write_read(address, &[reg], &mut data)
+            .map_err(|e| self.bus_error(address, e))?;
         Ok(u16::from_le_bytes(data))
     }
 
-    pub(super) fn write_named_register(&mut self, reg: Register, code: u16) -> Result<(), E> {
+    pub(super) fn write_named_register(
+        &mut self,
+        reg: Register,
+        code: u16,
+    ) -> Result<(), Error<E>> {
         self.write_register(reg as u8, self.address, code)
     }
 
@@ -31,33 +106,102 @@ where
         code: u16,
     ) -> Result<(), Error<E>> {
         self.write_register(reg as u8, self.address_nvm, code)?;
-        let mut c: u16 = 0;
+        self.wait_for_nvm(reg)
+    }
+
+    /// Read consecutive nonvolatile registers starting at start into out
+    pub(super) fn read_nvm_table(
+        &mut self,
+        start: RegisterNvm,
+        out: &mut [u16],
+    ) -> Result<(), Error<E>> {
+        for (i, chunk) in out.chunks_mut(MAX_BLOCK_WORDS).enumerate() {
+            let reg = start as u8 + (i * MAX_BLOCK_WORDS) as u8;
+            self.read_block(reg, self.address_nvm, chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Write consecutive nonvolatile registers starting at start
+    pub(super) fn write_nvm_table(
+        &mut self,
+        start: RegisterNvm,
+        data: &[u16],
+    ) -> Result<(), Error<E>> {
+        for (i, code) in data.iter().enumerate() {
+            self.write_register(start as u8 + i as u8, self.address_nvm, *code)?;
+        }
+        self.wait_for_nvm(start)
+    }
+
+    /// Write a nonvolatile shadow register by the low byte of its address
+    pub(super) fn write_nvm_address(&mut self, reg: u8, code: u16) -> Result<(), Error<E>> {
+        self.write_register(reg, self.address_nvm, code)
+    }
+
+    /// Read a nonvolatile shadow register by the low byte of its address
+    pub(super) fn read_nvm_address(&mut self, reg: u8) -> Result<u16, Error<E>> {
+        self.read_register(reg, self.address_nvm)
+    }
+
+    /// Send a nonvolatile memory command and wait for it to finish.
+    /// reg is only used to identify the failing register in the returned error,
+    /// see NvmSequence::command.
+    pub(super) fn send_nvm_command(
+        &mut self,
+        command: CommandCode,
+        reg: Option<RegisterNvm>,
+    ) -> Result<(), Error<E>> {
+        let mut sequence = NvmSequence::command(command, reg);
+        self.run_sequence(|read| sequence.step(read))
+    }
+
+    fn wait_for_nvm(&mut self, reg: RegisterNvm) -> Result<(), Error<E>> {
+        let mut sequence = NvmSequence::wait(reg);
+        self.run_sequence(|read| sequence.step(read))
+    }
+
+    /// Perform the actions of a sequence until it is done or fails
+    pub(super) fn run_sequence<F>(&mut self, mut step: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(u16) -> Result<Action, Error<E>>,
+    {
+        let mut read = 0;
         loop {
-            c += 1;
-            if !has_code(
-                CommStatCode::NonvolatileBusy as u16,
-                self.read_named_register(Register::CommStat)?,
-            ) {
-                break;
-            };
-            if c == MAX_LOOP {
-                return Err(Error::Timeout);
+            match step(read)? {
+                Action::Write(reg, code) => self.write_named_register(reg, code)?,
+                Action::Read(reg) => read = self.read_named_register(reg)?,
+                Action::Delay(us) => self.delay.delay_us(us),
+                Action::Done => return Ok(()),
             }
         }
-        if has_code(
-            CommStatCode::NonvolatileError as u16,
-            self.read_named_register(Register::CommStat)?,
-        ) {
-            return Err(Error::NonvolatileError(reg));
-        };
-
-        Ok(())
     }
 
-    fn write_register(&mut self, reg: u8, address: u8, code: u16) -> Result<(), E> {
+    fn write_register(&mut self, reg: u8, address: u8, code: u16) -> Result<(), Error<E>> {
         let mut buffer = [0];
-        let code = code.to_be_bytes();
-        let bytes: [u8; 3] = [reg, code[0], code[1]];
-        self.com.write_read(address, &bytes, &mut buffer)
+        let bytes = write_frame(reg, code);
+        self.count_transaction();
+        self.com
+            .write_read(address, &bytes, &mut buffer)
+            .map_err(|e| self.bus_error(address, e))
+    }
+
+    /// Bus errors at the NVM address are reported separately, since a board
+    /// can reach the main address while the NVM address is missing or strapped
+    /// wrong
+    fn bus_error(&self, address: u8, error: E) -> Error<E> {
+        if address == self.address_nvm && address != self.address {
+            Error::NvmAddressUnreachable(error)
+        } else {
+            Error::BusError(error)
+        }
+    }
+
+    #[inline(always)]
+    fn count_transaction(&mut self) {
+        #[cfg(feature = "transaction-count")]
+        {
+            self.transactions = self.transactions.wrapping_add(1);
+        }
     }
 }
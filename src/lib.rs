@@ -3,7 +3,7 @@
 //!
 //! for more examples please see [max17320_stm32f401_examples](https://github.com/shaoyuancc/max17320_stm32f401_examples)
 //!
-//! ```rust
+//! ```ignore
 //! #![no_std]
 //! #![no_main]
 //!
@@ -61,6 +61,8 @@
 //!     panic!("{:#?}", ef);
 //! }
 //! ```
+//!
+//! `MAX17320::new` does not wait between polls of the chip, so it is only suitable for reads and register writes. Operations that wait on the chip (`set_cell_model`, `set_design_capacity`, `commit_nonvolatile_memory` and the other NVM commands) time out without a delay; create the driver with `MAX17320::with_delay` and a real `DelayUs` implementation, such as your HAL's SysTick delay, to use them.
 
 //! ## References
 //! [MAX17320 datasheet](https://datasheets.maximintegrated.com/en/ds/MAX17320.pdf)
@@ -68,8 +70,10 @@
 //! ## Additional Notes
 //! - Only tested with STM32F401 microcontroller
 //! - 1-Wire communication protocol not implemented yet. Drop me an email or submit a pull request to add support.
+//! - Per-cell balancing timers and balancing current are not reported by the MAX17320, so no balancing detail read is provided.
+//! - Code size: every driver method is generic over the I2C bus, so only the methods a firmware calls are compiled into it. A consumer that only reads voltage, current and SOC does not pull in the NVM, command or async code, and no `minimal` feature is needed. `#![allow(dead_code)]` only silences warnings and does not affect what is linked. `scripts/check_code_size.sh` checks this: it builds `examples/minimal_reads` and fails if any NVM code is linked into it.
 
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![deny(
     missing_docs,
     missing_debug_implementations,
@@ -82,23 +86,112 @@
 )]
 #![allow(dead_code)]
 
+#[cfg(feature = "async")]
+mod asynch;
+mod battery;
 mod config;
 mod error;
 mod i2c_interface;
 mod register;
+#[cfg(feature = "serde")]
+mod register_map;
+mod sequence;
+mod status;
 
+#[cfg(feature = "async")]
+pub use asynch::MAX17320Async;
+pub use battery::Battery;
 pub use config::*;
+use core::time::Duration;
+use embedded_hal::blocking::delay::DelayUs;
 use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
-use error::Error;
+pub use error::Error;
 use register::*;
+pub use register::{ProtStatusCode, Register, RegisterNvm, RegisterSecondary};
+#[cfg(feature = "serde")]
+pub use register_map::RegisterMap;
+use sequence::ModelRefreshSequence;
+pub use status::*;
+
+/// Whether a register value looks like an idle or stuck bus rather than real
+/// data (0x0000 or 0xFFFF)
+pub fn looks_like_no_device(value: u16) -> bool {
+    value == 0x0000 || value == 0xFFFF
+}
+
+/// Currents within ± this (mA) count as neither charging nor discharging, so
+/// measurement noise at rest does not flip between the two
+pub const CURRENT_DEADBAND_MA: f32 = 20.0;
+
+/// Fraction of capacity lost per °C below CAPACITY_REFERENCE_C, used by
+/// read_capacity_at_temperature. A typical value for Li-ion cells at moderate
+/// discharge rates.
+pub const CAPACITY_TEMPCO_PER_C: f32 = 0.006;
+
+/// Temperature (°C) above which capacity is treated as independent of temperature
+pub const CAPACITY_REFERENCE_C: f32 = 25.0;
+
+/// Nominal Li-ion cell voltage (V) used for pack ratings
+pub const NOMINAL_CELL_VOLTAGE_V: f32 = 3.6;
+
+/// MixSOC/VFSOC divergence (percentage points) above which read_gauge_health
+/// reports that the gauge needs recalibrating
+pub const GAUGE_DIVERGENCE_LIMIT: f32 = 10.0;
+
+/// Number of registers in the nonvolatile shadow RAM (0x180-0x1EF)
+pub const NVM_SHADOW_LEN: usize = 112;
+
+/// Low byte of the first nonvolatile shadow register, on the NVM I2C address
+const NVM_SHADOW_START: u8 = 0x80;
+
+/// Registers restored by restore_nonvolatile_memory (0x180-0x1DF). The rest of
+/// the image, 0x1E0-0x1EF, holds the serial number, ROM ID and the NVM update
+/// counter, which are read-only.
+const NVM_WRITABLE_LEN: usize = 96;
+
+/// Number of entries in the custom cell model OCV table
+pub const OCV_TABLE_LEN: usize = 12;
+
+/// Value of the DevName register on a genuine MAX17320
+pub const MAX17320_DEVICE_NAME: u16 = 0x4209;
+
+/// DevName bits 3:0 hold the silicon revision, the rest identify the part
+const DEVICE_NAME_REVISION_MASK: u16 = 0x000F;
+
+/// Check a value read with read_device_name against MAX17320_DEVICE_NAME,
+/// ignoring the silicon revision bits
+pub fn is_device_name_valid(name: u16) -> bool {
+    name & !DEVICE_NAME_REVISION_MASK == MAX17320_DEVICE_NAME & !DEVICE_NAME_REVISION_MASK
+}
 
 /// MAX17320 interface
 #[derive(Debug, Clone, Copy)]
-pub struct MAX17320<I2C: Write + WriteRead> {
+pub struct MAX17320<I2C: Write + WriteRead, D = NoDelay> {
     com: I2C,
+    delay: D,
     address: u8,
     address_nvm: u8,
     r_sense: f32,
+    soc_display_range: (f32, f32),
+    write_unlocked: bool,
+    protected_pages: WriteProtectionPages,
+    last_qh: Option<i16>,
+    was_full: bool,
+    last_full_charge: Option<FullChargeInfo>,
+    energy: CumulativeEnergy,
+    #[cfg(feature = "transaction-count")]
+    transactions: u32,
+}
+
+/// Delay provider that does not wait. Used when the driver is created without
+/// a delay, in which case NVM commands and model refreshes poll the chip back
+/// to back and give up after a fixed number of polls, long before the chip is
+/// done.
+#[derive(Debug, Clone, Copy)]
+pub struct NoDelay;
+
+impl DelayUs<u32> for NoDelay {
+    fn delay_us(&mut self, _us: u32) {}
 }
 
 impl<I2C, E> MAX17320<I2C>
@@ -106,32 +199,96 @@ where
     I2C: WriteRead<Error = E> + Write<Error = E> + Read<Error = E>,
 {
     /// Create new driver interface. r_sense is in mΩ.
+    ///
+    /// The driver is created with NoDelay, which is fine for reads and register
+    /// writes but not for anything that waits on the chip: set_cell_model,
+    /// set_design_capacity, commit_nonvolatile_memory and the other NVM commands
+    /// give up after 500 back-to-back polls and return an error. Use with_delay
+    /// with a real DelayUs for those.
     pub fn new(i2c: I2C, r_sense_mohm: f32) -> Result<Self, Error<E>> {
         MAX17320::with_addresses(i2c, 0x36, 0x0B, r_sense_mohm)
     }
 
     /// Create new driver interface with specific I2C address. r_sense is in mΩ.
+    /// Like new, this uses NoDelay, see there.
     pub fn with_addresses(
         i2c: I2C,
         address: u8,
         address_nvm: u8,
         r_sense_mohm: f32,
+    ) -> Result<Self, Error<E>> {
+        MAX17320::with_addresses_and_delay(i2c, NoDelay, address, address_nvm, r_sense_mohm)
+    }
+}
+
+impl<I2C, D, E> MAX17320<I2C, D>
+where
+    I2C: WriteRead<Error = E> + Write<Error = E> + Read<Error = E>,
+    D: DelayUs<u32>,
+{
+    /// Create new driver interface that uses delay to respect the datasheet's
+    /// minimum wait times for nonvolatile memory operations. r_sense is in mΩ.
+    ///
+    /// A real delay is required for set_cell_model, set_design_capacity,
+    /// commit_nonvolatile_memory and the other NVM commands; without one they
+    /// poll too fast and time out.
+    pub fn with_delay(i2c: I2C, delay: D, r_sense_mohm: f32) -> Result<Self, Error<E>> {
+        MAX17320::with_addresses_and_delay(i2c, delay, 0x36, 0x0B, r_sense_mohm)
+    }
+
+    /// Create new driver interface with specific I2C address and a delay. r_sense is in mΩ.
+    pub fn with_addresses_and_delay(
+        i2c: I2C,
+        delay: D,
+        address: u8,
+        address_nvm: u8,
+        r_sense_mohm: f32,
     ) -> Result<Self, Error<E>> {
         let chip = Self {
             com: i2c,
+            delay,
             address,
             address_nvm,
             r_sense: r_sense_mohm,
+            soc_display_range: (0.0, 100.0),
+            write_unlocked: false,
+            protected_pages: WriteProtectionPages::ALL,
+            last_qh: None,
+            was_full: false,
+            last_full_charge: None,
+            energy: CumulativeEnergy::default(),
+            #[cfg(feature = "transaction-count")]
+            transactions: 0,
         };
         Ok(chip)
     }
 
+    /// Number of I2C transactions issued since creation or the last reset.
+    /// Wraps around on overflow.
+    #[cfg(feature = "transaction-count")]
+    pub fn transaction_count(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Reset the I2C transaction counter to zero
+    #[cfg(feature = "transaction-count")]
+    pub fn reset_transaction_count(&mut self) {
+        self.transactions = 0;
+    }
+
     /// Read the device name
     pub fn read_device_name(&mut self) -> Result<u16, Error<E>> {
-        let name = self.read_named_register(Register::DevName)?;
+        let name = self.read_named_register_checked(Register::DevName)?;
         Ok(name)
     }
 
+    /// Read the silicon/firmware revision. The MAX17320 has no separate version
+    /// register; the revision is reported in the low bits of DevName.
+    pub fn read_firmware_version(&mut self) -> Result<u16, Error<E>> {
+        let name = self.read_device_name()?;
+        Ok(name & DEVICE_NAME_REVISION_MASK)
+    }
+
     /// Read alert status and chip status
     pub fn read_status(&mut self) -> Result<u16, Error<E>> {
         let val = self.read_named_register(Register::Status)?;
@@ -144,36 +301,436 @@ where
         Ok(convert_to_capacity(raw, self.r_sense))
     }
 
+    /// Estimate the remaining capacity (mAh) if the pack were at temp_c instead of
+    /// its present temperature.
+    ///
+    /// The gauge only models capacity at the temperature it is measuring, so this
+    /// is a host side projection: RepCap is scaled by
+    /// 1 - CAPACITY_TEMPCO_PER_C * (CAPACITY_REFERENCE_C - T) at each temperature
+    /// (no derating above CAPACITY_REFERENCE_C). Treat it as a warning level
+    /// estimate, e.g. for "expect less runtime in the cold".
+    pub fn read_capacity_at_temperature(&mut self, temp_c: f32) -> Result<f32, Error<E>> {
+        let capacity = self.read_capacity()?;
+        let now = self.read_temperature()?;
+        Ok(project_capacity(capacity, now, temp_c))
+    }
+
+    /// Estimate how long the pack would run at a constant load_ma discharge
+    /// current (positive, mA) if it were at temp_c.
+    ///
+    /// The load is written to AtRate as a discharge current and, after one task
+    /// period (351.5ms, so create the driver with a real delay), the gauge's own
+    /// at-rate time to empty is read from AtTTE. That estimate is then scaled by
+    /// the same temperature derating as read_capacity_at_temperature. A load
+    /// that is not finite and positive, or that does not fit AtRate for this
+    /// r_sense, returns Error::InvalidLoadCurrent.
+    pub fn read_runtime_projection(
+        &mut self,
+        load_ma: f32,
+        temp_c: f32,
+    ) -> Result<Duration, Error<E>> {
+        if !(load_ma.is_finite() && load_ma > 0.0) {
+            return Err(Error::InvalidLoadCurrent);
+        }
+        let code = -load_ma * self.r_sense / CURRENT_LSB_UV - 0.5;
+        if !(i16::MIN as f32..=-1.0).contains(&code) {
+            return Err(Error::InvalidLoadCurrent);
+        }
+        self.write_named_register(Register::AtRate, code as i16 as u16)?;
+        self.delay.delay_us(CURRENT_UPDATE_PERIOD_US);
+        let at_tte = convert_to_time(self.read_named_register(Register::AtTte)?);
+        let now = self.read_temperature()?;
+        Ok(Duration::from_secs_f32(project_capacity(
+            at_tte, now, temp_c,
+        )))
+    }
+
+    /// Read the learned full capacity (mAh)
+    pub fn read_full_capacity(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register(Register::FullCapRep)?;
+        Ok(convert_to_capacity(raw, self.r_sense))
+    }
+
+    /// Read the pack voltage, current, temperature and SOC together with the Timer
+    /// register, so loggers can drop samples taken before the gauge updated
+    pub fn read_sample(&mut self) -> Result<Sample, Error<E>> {
+        let timer = self.read_named_register(Register::Timer)?;
+        Ok(Sample {
+            timer,
+            pack_voltage: self.read_batt()?,
+            current_ma: self.read_current()?,
+            temperature: self.read_temperature()?,
+            state_of_charge: self.read_state_of_charge()?,
+        })
+    }
+
+    /// Watchdog check for a hung gauge: take a new sample and report whether
+    /// anything moved since previous (see Sample::has_changed_from for the
+    /// deadbands). The new sample is returned for the next check.
+    ///
+    /// The Timer register advances every 175.8ms on a running gauge, so with
+    /// samples more than that apart a false result means the gauge has frozen
+    /// even if the pack is idle and the measurements are legitimately flat.
+    pub fn has_telemetry_changed(&mut self, previous: &Sample) -> Result<(bool, Sample), Error<E>> {
+        let sample = self.read_sample()?;
+        Ok((sample.has_changed_from(previous), sample))
+    }
+
+    /// Read how long the gauge has been operating. Timer counts 175.8ms per LSB
+    /// and rolls over into TimerH, which counts 3.2h per LSB, for a range of
+    /// about 24 years.
+    pub fn read_operating_time(&mut self) -> Result<Duration, Error<E>> {
+        let mut high = self.read_named_register(Register::TimerH)?;
+        let mut low = self.read_named_register(Register::Timer)?;
+        // Timer may have rolled over into TimerH between the two reads
+        let high_after = self.read_named_register(Register::TimerH)?;
+        if high_after != high {
+            high = high_after;
+            low = self.read_named_register(Register::Timer)?;
+        }
+        Ok(convert_to_operating_time(high, low))
+    }
+
+    /// Update and read the energy charged into and discharged from the pack.
+    ///
+    /// The MAX17320 has no energy accumulator, so this is a software integration
+    /// in the driver: each call takes the change in QH since the previous call and
+    /// multiplies it by the pack open circuit voltage (VFOCV x cell count). The
+    /// first call only records the starting point. Call it often enough that the
+    /// voltage does not move much between calls, and at least once per QH wrap.
+    pub fn read_cumulative_energy(&mut self) -> Result<CumulativeEnergy, Error<E>> {
+        let qh = self.read_named_register_signed(Register::QH)?;
+        let pack_ocv = self.read_vfocv()? * self.read_cell_count()? as f32;
+        if let Some(last) = self.last_qh {
+            let delta_mah = convert_to_charge(qh.wrapping_sub(last), self.r_sense);
+            let wh = delta_mah * pack_ocv / 1000.0;
+            if wh > 0.0 {
+                self.energy.charged_wh += wh;
+            } else {
+                self.energy.discharged_wh -= wh;
+            }
+        }
+        self.last_qh = Some(qh);
+        Ok(self.energy)
+    }
+
+    /// Zero the cumulative energy counters and restart the integration
+    pub fn reset_cumulative_energy(&mut self) {
+        self.last_qh = None;
+        self.energy = CumulativeEnergy::default();
+    }
+
+    /// Read the raw coulomb counter QH (mAh). This is the net charge that has
+    /// flowed in (positive) or out (negative) and wraps around when it overflows.
+    pub fn read_accumulated_charge(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_signed(Register::QH)?;
+        Ok(convert_to_charge(raw, self.r_sense))
+    }
+
     /// Read reported state of charge (%)
     pub fn read_state_of_charge(&mut self) -> Result<f32, Error<E>> {
         let raw = self.read_named_register(Register::RepSoc)?;
         Ok(convert_to_percentage(raw))
     }
 
+    /// Set the RepSOC range reported as 0% and 100% by read_display_state_of_charge,
+    /// e.g. (5.0, 100.0) to hold back a 5% reserve.
+    ///
+    /// The MAX17320 has no register for this (its empty point is set by VEmpty and
+    /// its full point by full detection), so the mapping is done by the driver and
+    /// is not persisted. SOC alert thresholds still use the unmapped RepSOC.
+    pub fn set_soc_display_range(&mut self, empty_pct: f32, full_pct: f32) -> Result<(), Error<E>> {
+        if !(0.0..full_pct).contains(&empty_pct) || full_pct > 100.0 {
+            return Err(Error::InvalidConfigurationValue(empty_pct as u16));
+        }
+        self.soc_display_range = (empty_pct, full_pct);
+        Ok(())
+    }
+
+    /// The RepSOC range reported as 0% and 100%, returns tuple of (empty_pct, full_pct)
+    pub fn soc_display_range(&self) -> (f32, f32) {
+        self.soc_display_range
+    }
+
+    /// Read RepSOC rescaled to the display range (%), clamped to 0-100
+    pub fn read_display_state_of_charge(&mut self) -> Result<f32, Error<E>> {
+        let soc = self.read_state_of_charge()?;
+        Ok(rescale_soc(soc, self.soc_display_range))
+    }
+
     /// Read the cell voltage for a single cell (v)
     pub fn read_vcell(&mut self) -> Result<f32, Error<E>> {
-        let raw = self.read_named_register(Register::VCell)?;
+        let raw = self.read_named_register_checked(Register::VCell)?;
+        Ok(convert_to_voltage(raw))
+    }
+
+    /// Compare the coulomb counter based MixSOC against the voltage based VFSOC.
+    /// A large, persistent gap means the gauge has drifted or is misconfigured.
+    pub fn read_gauge_health(&mut self) -> Result<GaugeHealth, Error<E>> {
+        let mix_soc = convert_to_percentage(self.read_named_register(Register::MixSoc)?);
+        let vf_soc = convert_to_percentage(self.read_named_register(Register::VFSoc)?);
+        let divergence = (mix_soc - vf_soc).abs();
+        Ok(GaugeHealth {
+            mix_soc,
+            vf_soc,
+            divergence,
+            needs_recalibration: divergence > GAUGE_DIVERGENCE_LIMIT,
+        })
+    }
+
+    /// Read the voltage fuel gauge's open circuit voltage estimate for a single cell (V)
+    pub fn read_vfocv(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register(Register::VFOcv)?;
+        Ok(convert_to_voltage(raw))
+    }
+
+    /// Estimate the state of charge (%) from VFOCV using a caller provided
+    /// open circuit voltage table of (cell voltage, SOC %) pairs sorted by voltage.
+    ///
+    /// This is independent of the coulomb counter and is meant as a sanity check
+    /// against RepSOC. Voltages outside the table are clamped to its end points.
+    pub fn estimate_soc_from_voltage(&mut self, ocv_table: &[(f32, f32)]) -> Result<f32, Error<E>> {
+        if ocv_table.is_empty() {
+            return Err(Error::InvalidConfigurationValue(0));
+        }
+        let ocv = self.read_vfocv()?;
+        Ok(interpolate_ocv_table(ocv_table, ocv))
+    }
+
+    /// Read the averaged cell voltage for a single cell (V)
+    pub fn read_average_vcell(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register(Register::AvgVCell)?;
         Ok(convert_to_voltage(raw))
     }
 
     /// Read temperature (°C)
     pub fn read_temperature(&mut self) -> Result<f32, Error<E>> {
-        let raw = self.read_named_register(Register::Temp)? as i16;
+        let raw = self.read_named_register_signed(Register::Temp)?;
         Ok(convert_to_temperature(raw))
     }
 
     /// Read internal die temperature (°C)
     pub fn read_die_temperature(&mut self) -> Result<f32, Error<E>> {
-        let raw = self.read_named_register(Register::DieTemp)? as i16;
+        let raw = self.read_named_register_signed(Register::DieTemp)?;
+        Ok(convert_to_temperature(raw))
+    }
+
+    /// Read the die temperature minus the thermistor temperature (°C), read back
+    /// to back. A large or growing gap points at a thermistor fault or at the
+    /// cells heating faster than the board.
+    pub fn read_thermal_delta(&mut self) -> Result<f32, Error<E>> {
+        let die = self.read_named_register_signed(Register::DieTemp)?;
+        let thermistor = self.read_named_register_signed(Register::Temp)?;
+        Ok(convert_to_temperature(die) - convert_to_temperature(thermistor))
+    }
+
+    /// Read how far the die is below its overtemperature trip (°C). The die
+    /// overtemperature protection (ProtStatus.DieHot) shares the TooHotDischarge
+    /// threshold in nTPrtTh3 (low byte, 1°C LSB). Negative once past the trip.
+    pub fn read_die_thermal_state(&mut self) -> Result<f32, Error<E>> {
+        let die = self.read_die_temperature()?;
+        let code = self.read_named_register_nvm(RegisterNvm::NTPrtTh3)?;
+        let threshold = code.to_be_bytes()[1] as i8 as f32;
+        Ok(threshold - die)
+    }
+
+    /// Read the temperature of the protection FETs (°C) from the thermistor
+    /// channel (1-4) mounted on them.
+    ///
+    /// The MAX17320 drives external FETs and has no FET temperature or RDS(on)
+    /// register; its die temperature is the gauge IC's own. FET self-heating is
+    /// measured by placing one of the TH1-TH4 thermistors on the FETs and reading
+    /// that channel's Temp1-Temp4 register (0x13A-0x137, 1/256°C LSB).
+    pub fn read_protector_temperature(&mut self, channel: u8) -> Result<f32, Error<E>> {
+        let reg = match channel {
+            1 => RegisterSecondary::Temp1,
+            2 => RegisterSecondary::Temp2,
+            3 => RegisterSecondary::Temp3,
+            4 => RegisterSecondary::Temp4,
+            _ => return Err(Error::InvalidConfigurationValue(channel as u16)),
+        };
+        let raw = self.read_named_register_secondary_signed(reg)?;
         Ok(convert_to_temperature(raw))
     }
 
-    /// Read battery current (A)
+    /// Read any register along with its decoded value, for register explorers
+    /// and debugging. Registers without a physical unit decode as PhysicalValue::Raw.
+    pub fn read_named_with_raw(&mut self, reg: Register) -> Result<(u16, PhysicalValue), Error<E>> {
+        let raw = self.read_named_register(reg)?;
+        Ok((raw, physical_value(reg, raw, self.r_sense)))
+    }
+
+    /// Read temperature (°F)
+    pub fn read_temperature_fahrenheit(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_signed(Register::Temp)?;
+        Ok(convert_to_temperature_fahrenheit(raw))
+    }
+
+    /// Read internal die temperature (°F)
+    pub fn read_die_temperature_fahrenheit(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_signed(Register::DieTemp)?;
+        Ok(convert_to_temperature_fahrenheit(raw))
+    }
+
+    /// Read battery current (mA). Positive when charging.
     pub fn read_current(&mut self) -> Result<f32, Error<E>> {
-        let raw = self.read_named_register(Register::Current)? as i16;
+        let raw = self.read_named_register_signed(Register::Current)?;
+        Ok(convert_to_current(raw, self.r_sense))
+    }
+
+    /// Read the signed C-rate: Current divided by the learned full capacity
+    /// (FullCapRep). Positive when charging; 0 if no full capacity is known.
+    pub fn read_c_rate(&mut self) -> Result<f32, Error<E>> {
+        let current_ma = self.read_current()?;
+        let full_cap_mah = self.read_full_capacity()?;
+        Ok(c_rate(current_ma, full_cap_mah))
+    }
+
+    /// Read the average battery current (mA). Positive when charging.
+    pub fn read_average_current(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_signed(Register::AvgCurrent)?;
+        Ok(convert_to_current(raw, self.r_sense))
+    }
+
+    /// Take samples Current readings and return their mean (mA), with the highest
+    /// and lowest reading discarded as outliers when there are at least 3.
+    ///
+    /// Readings are spaced by the Current update period (351.5ms) using the
+    /// driver's delay, so a burst of N samples takes about N x 351.5ms. Without a
+    /// delay (NoDelay) the readings are back to back and mostly repeat the same
+    /// update.
+    pub fn read_current_averaged(&mut self, samples: u8) -> Result<f32, Error<E>> {
+        if samples == 0 {
+            return Err(Error::InvalidConfigurationValue(0));
+        }
+        let mut sum = 0.0;
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for i in 0..samples {
+            if i > 0 {
+                self.delay.delay_us(CURRENT_UPDATE_PERIOD_US);
+            }
+            let current = self.read_current()?;
+            sum += current;
+            min = min.min(current);
+            max = max.max(current);
+        }
+        if samples >= 3 {
+            Ok((sum - min - max) / (samples - 2) as f32)
+        } else {
+            Ok(sum / samples as f32)
+        }
+    }
+
+    /// Read Current and AvgCurrent in one block transaction so both come from
+    /// the same gauge update, returns tuple of (instantaneous_ma, average_ma)
+    pub fn read_current_pair(&mut self) -> Result<(f32, f32), Error<E>> {
+        let mut words = [0; 2];
+        self.read_block(Register::Current as u8, self.address, &mut words)?;
+        Ok((
+            convert_to_current(words[0] as i16, self.r_sense),
+            convert_to_current(words[1] as i16, self.r_sense),
+        ))
+    }
+
+    /// Read VCell and AvgVCell in one block transaction so both come from the
+    /// same gauge update, returns tuple of (instantaneous_v, average_v)
+    pub fn read_voltage_pair(&mut self) -> Result<(f32, f32), Error<E>> {
+        // AvgVCell (0x19) directly precedes VCell (0x1A)
+        let mut words = [0; 2];
+        self.read_block(Register::AvgVCell as u8, self.address, &mut words)?;
+        Ok((convert_to_voltage(words[1]), convert_to_voltage(words[0])))
+    }
+
+    /// Check whether the current measurement looks trustworthy, see
+    /// CurrentSenseHealth for what is checked
+    pub fn read_current_sense_health(&mut self) -> Result<CurrentSenseHealth, Error<E>> {
+        let current = self.read_named_register_signed(Register::Current)?;
+        let avg_current = self.read_named_register_signed(Register::AvgCurrent)?;
+        let saturated = [current, avg_current]
+            .iter()
+            .any(|raw| *raw == i16::MAX || *raw == i16::MIN);
+        Ok(CurrentSenseHealth {
+            current_ma: convert_to_current(current, self.r_sense),
+            avg_current_ma: convert_to_current(avg_current, self.r_sense),
+            saturated,
+        })
+    }
+
+    /// Read the charge termination current (mA) from nIChgTerm. Full detection
+    /// requires the charge current to fall below this; it uses the same LSB as
+    /// Current (1.5625µV / r_sense).
+    pub fn read_charge_termination_current(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_nvm_signed(RegisterNvm::NIChgTerm)?;
         Ok(convert_to_current(raw, self.r_sense))
     }
 
+    /// Read the charge current the gauge recommends to the charger (mA), from
+    /// ChargingCurrent. This is the operating recommendation after step charging
+    /// and temperature derating, not the overcharge current protection trip. It
+    /// uses the same LSB as Current (1.5625µV / r_sense).
+    pub fn read_recommended_charge_current(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_signed(Register::ChargingCurrent)?;
+        Ok(convert_to_current(raw, self.r_sense))
+    }
+
+    /// Read the charge voltage the gauge recommends to the charger, from
+    /// ChargingVoltage. The register is per cell (0.078125mV LSB, like VCell), so
+    /// this returns (per cell, pack) in V with the pack value scaled by the
+    /// configured cell count.
+    pub fn read_recommended_charge_voltage(&mut self) -> Result<(f32, f32), Error<E>> {
+        let raw = self.read_named_register(Register::ChargingVoltage)?;
+        let n_cells = self.read_cell_count()?;
+        let cell_v = convert_to_voltage(raw);
+        Ok((cell_v, cell_v * n_cells as f32))
+    }
+
+    /// Whether the pack is charging, i.e. current is above CURRENT_DEADBAND_MA
+    pub fn is_charging(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_current()? > CURRENT_DEADBAND_MA)
+    }
+
+    /// Whether the pack is discharging, i.e. current is below -CURRENT_DEADBAND_MA
+    pub fn is_discharging(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_current()? < -CURRENT_DEADBAND_MA)
+    }
+
+    /// Read the current measurement calibration, returns tuple of (gain, offset_ma)
+    pub fn read_current_calibration(&mut self) -> Result<(f32, f32), Error<E>> {
+        let gain = self.read_named_register_signed(Register::CGain)?;
+        let offset = self.read_named_register_signed(Register::COff)?;
+        Ok((
+            gain as f32 / CGAIN_UNITY,
+            convert_to_current(offset, self.r_sense),
+        ))
+    }
+
+    /// Set the current measurement gain (1.0 = no correction, -32.0 to 31.99)
+    /// and offset (mA) applied to Current and AvgCurrent.
+    ///
+    /// The offset is added to the measurement, so to null a reading of +30mA at
+    /// zero load pass offset_ma = -30.0.
+    ///
+    /// CGain and COff are volatile: they return to their power-up values after
+    /// a reset, so this has to be called again after every reset. For a one-time
+    /// factory calibration, write the NVM shadow copies instead (write_nvm_checked
+    /// or apply_register_map) and commit them with commit_nonvolatile_memory.
+    pub fn set_current_calibration(&mut self, gain: f32, offset_ma: f32) -> Result<(), Error<E>> {
+        let gain_code = gain * CGAIN_UNITY;
+        if !(i16::MIN as f32..=i16::MAX as f32).contains(&gain_code) {
+            return Err(Error::InvalidConfigurationValue(gain as u16));
+        }
+        let offset_code = offset_ma * self.r_sense / CURRENT_LSB_UV;
+        if !(i16::MIN as f32..=i16::MAX as f32).contains(&offset_code) {
+            return Err(Error::InvalidConfigurationValue(offset_ma as u16));
+        }
+        self.begin_write()?;
+        self.write_named_register(Register::CGain, gain_code as i16 as u16)?;
+        self.write_named_register(Register::COff, offset_code as i16 as u16)?;
+        self.end_write()?;
+        Ok(())
+    }
+
     /// Read time to empty (seconds)
     pub fn read_time_to_empty(&mut self) -> Result<f32, Error<E>> {
         let raw = self.read_named_register(Register::TimeToEmpty)?;
@@ -186,12 +743,128 @@ where
         Ok(convert_to_time(raw))
     }
 
+    /// Read the fuel gauge status flags (FStat)
+    pub fn read_fstat_flags(&mut self) -> Result<FStatFlags, Error<E>> {
+        let val = self.read_named_register(Register::FStat)?;
+        Ok(FStatFlags::from_bits(val))
+    }
+
+    /// Read whether measurements are being produced.
+    ///
+    /// The MAX17320 has no conversion-complete flag beyond FStat.DNR, so the
+    /// Timer register is returned alongside it; comparing two reads of it is
+    /// the reliable way to detect a frozen gauge.
+    pub fn read_measurement_status(&mut self) -> Result<MeasurementStatus, Error<E>> {
+        let fstat = self.read_named_register(Register::FStat)?;
+        let timer = self.read_named_register(Register::Timer)?;
+        Ok(MeasurementStatus {
+            data_ready: !has_code(FStatCode::DataNotReady as u16, fstat),
+            timer,
+        })
+    }
+
+    /// Seed the gauge with a known state of charge (%), e.g. from an independent
+    /// reference after a pack swap.
+    ///
+    /// Writing RepSOC alone is overwritten on the next update, so the mixing,
+    /// averaged and reported capacities (MixCap, AvCap, RepCap) are all set to
+    /// percent of FullCapRep, which the SOC registers are derived from.
+    pub fn force_state_of_charge(&mut self, percent: f32) -> Result<(), Error<E>> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(Error::InvalidConfigurationValue(percent as u16));
+        }
+        let full_cap = self.read_named_register(Register::FullCapRep)?;
+        let cap = (full_cap as f32 * percent / 100.0) as u16;
+        self.begin_write()?;
+        self.write_named_register(Register::MixCap, cap)?;
+        self.write_named_register(Register::AvCap, cap)?;
+        self.write_named_register(Register::RepCap, cap)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Restart capacity learning by reinitialising the dQAcc/dPAcc accumulators
+    /// to FullCapNom / 16 and 200% as in the datasheet initialisation procedure.
+    pub fn reset_capacity_learning(&mut self) -> Result<(), Error<E>> {
+        let full_cap_nom = self.read_named_register(Register::FullCapNom)?;
+        self.begin_write()?;
+        self.write_named_register(Register::DQAcc, full_cap_nom / 16)?;
+        self.write_named_register(Register::DPAcc, 0x0C80)?;
+        self.end_write()?;
+        Ok(())
+    }
+
     /// Read fault status of the protection functionality
     pub fn read_protection_status(&mut self) -> Result<u16, Error<E>> {
         let val = self.read_named_register(Register::ProtStatus)?;
         Ok(val)
     }
 
+    /// If ProtStatus.CapOverflow is set, read the accumulated charge (QH) against
+    /// the full and design capacities, e.g. to spot a design capacity configured
+    /// below the real pack. Returns None if the fault is not active.
+    ///
+    /// RepCap is not used because the gauge caps it at FullCapRep, so it can
+    /// never show how far the charge went past full.
+    pub fn read_capacity_overflow_detail(
+        &mut self,
+    ) -> Result<Option<CapacityOverflowDetail>, Error<E>> {
+        let status = self.read_protection_status()?;
+        if !has_code(ProtStatusCode::CapacityOverflow as u16, status) {
+            return Ok(None);
+        }
+        Ok(Some(CapacityOverflowDetail {
+            accumulated_mah: self.read_accumulated_charge()?,
+            full_mah: self.read_full_capacity()?,
+            design_mah: self.read_design_capacity()?,
+        }))
+    }
+
+    /// Whether the protector's full detection has ended the charge
+    /// (ProtStatus.Full). This follows the protector's own end-of-charge logic
+    /// (charge voltage and termination current), not RepSOC reaching 100%.
+    ///
+    /// Also records FullChargeInfo when full detection first becomes true, see
+    /// read_last_full_charge_info.
+    pub fn is_fully_charged(&mut self) -> Result<bool, Error<E>> {
+        let status = self.read_protection_status()?;
+        let full = has_code(ProtStatusCode::Full as u16, status);
+        if full && !self.was_full {
+            self.last_full_charge = Some(FullChargeInfo {
+                cycles: self.read_named_register(Register::Cycles)? as f32 * CYCLES_LSB,
+                operating_time: self.read_operating_time()?,
+                full_capacity_mah: self.read_full_capacity()?,
+            });
+        }
+        self.was_full = full;
+        Ok(full)
+    }
+
+    /// Read when the pack was last fully charged, the reference point the gauge
+    /// learns capacity from. The chip does not store this, so the driver records
+    /// it whenever is_fully_charged sees full detection start; this method checks
+    /// once itself. Returns None if no full charge has been seen since the
+    /// driver was created, so poll is_fully_charged while charging.
+    pub fn read_last_full_charge_info(&mut self) -> Result<Option<FullChargeInfo>, Error<E>> {
+        self.is_fully_charged()?;
+        Ok(self.last_full_charge)
+    }
+
+    /// Decide whether the pack is safe to use, checking in order of severity:
+    ///
+    /// 1. PermanentFail: nBattStatus holds a permanent failure, or ProtStatus.PermFail is set
+    /// 2. Fault: any ProtStatus fault other than Ship and Full (end of charge is not a fault)
+    /// 3. Degraded: read_current_sense_health is not trusted, or read_gauge_health
+    ///    needs recalibration
+    /// 4. Ok otherwise
+    pub fn is_pack_healthy(&mut self) -> Result<PackHealth, Error<E>> {
+        let latch = self.read_battery_status()?;
+        let status = self.read_protection_status()?;
+        let current = self.read_current_sense_health()?;
+        let gauge = self.read_gauge_health()?;
+        Ok(classify_pack_health(latch, status, &current, &gauge))
+    }
+
     /// Read history of previous fault status of the protection functionality
     pub fn read_protection_alert(&mut self) -> Result<u16, Error<E>> {
         let val = self.read_named_register(Register::ProtAlrt)?;
@@ -204,6 +877,176 @@ where
         Ok(())
     }
 
+    /// Check for pending alerts without the ALRT pin. Reads Status and, if any
+    /// alert bit is set, the registers behind it; returns None if nothing is
+    /// pending.
+    ///
+    /// The reported gauge alert bits are cleared so the next poll only reports
+    /// new events (bits whose condition persists set again on the next update).
+    /// Status.PA is left set; handle it and call rearm_protection.
+    pub fn poll_alerts(&mut self) -> Result<Option<AlertReport>, Error<E>> {
+        let status = self.read_status()? & ALERT_STATUS_BITS;
+        if status == 0 {
+            return Ok(None);
+        }
+        let any = |mask: u16| status & mask != 0;
+        let protection = any(StatusCode::ProtectionAlert as u16);
+        let voltage =
+            any(StatusCode::MinVoltageExceeded as u16 | StatusCode::MaxVoltageExceeded as u16);
+        let temperature =
+            any(StatusCode::MinTemperatureExceeded as u16
+                | StatusCode::MaxTemperatureExceeded as u16);
+        let soc = any(StatusCode::MinSocExceeded as u16
+            | StatusCode::MaxSocExceeded as u16
+            | StatusCode::Soc1PercentChange as u16);
+        let current =
+            any(StatusCode::MinCurrentExceeded as u16 | StatusCode::MaxCurrentExceeded as u16);
+        let report = AlertReport {
+            status,
+            protection_alert: protection
+                .then(|| self.read_protection_alert())
+                .transpose()?,
+            cell_voltage_v: voltage.then(|| self.read_vcell()).transpose()?,
+            temperature_c: temperature.then(|| self.read_temperature()).transpose()?,
+            state_of_charge: soc.then(|| self.read_state_of_charge()).transpose()?,
+            current_ma: current.then(|| self.read_current()).transpose()?,
+        };
+        let gauge_bits = status & !(StatusCode::ProtectionAlert as u16);
+        if gauge_bits != 0 {
+            let current_status = self.read_status()?;
+            self.write_named_register(Register::Status, current_status & !gauge_bits)?;
+        }
+        Ok(Some(report))
+    }
+
+    /// Re-arm the protection state machine after a fault has cleared by clearing
+    /// ProtAlrt and then Status.ProtAlrt (in that order, as the datasheet requires).
+    ///
+    /// The FETs turn back on only once the fault condition itself has recovered
+    /// past its hysteresis threshold (e.g. the cells have cooled below the
+    /// overtemperature recovery point). Permanent failures, and faults whose
+    /// condition persists, are not cleared by this and need a reset instead.
+    /// CHGOff/DISOff overrides set via set_charge_fet/set_discharge_fet are kept.
+    pub fn rearm_protection(&mut self) -> Result<(), Error<E>> {
+        self.clear_protection_alert()?;
+        let status = self.read_named_register(Register::Status)?;
+        self.write_named_register(
+            Register::Status,
+            status & !(StatusCode::ProtectionAlert as u16),
+        )?;
+        Ok(())
+    }
+
+    /// Force the discharge FET off (enable = false) or return it to normal
+    /// protection control (enable = true) via CommStat.DISOff.
+    ///
+    /// Only takes effect if nProtCfg.CmOvrdEn is enabled.
+    pub fn set_discharge_fet(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.set_fet_override(CommStatCode::DischargeOff, !enable)
+    }
+
+    /// Force the charge FET off (enable = false) or return it to normal
+    /// protection control (enable = true) via CommStat.CHGOff.
+    ///
+    /// Only takes effect if nProtCfg.CmOvrdEn is enabled.
+    pub fn set_charge_fet(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.set_fet_override(CommStatCode::ChargeOff, !enable)
+    }
+
+    /// Same as set_discharge_fet, but polls the FET drive state afterwards and
+    /// returns Error::FetDidNotSwitch if the discharge FET did not reach the
+    /// requested state.
+    ///
+    /// Note that enabling can legitimately fail while a discharging protection
+    /// fault is active.
+    pub fn set_discharge_fet_confirmed(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.set_discharge_fet(enable)?;
+        self.wait_for_fet_state(FProtStatCode::DischargeFetOn, enable)
+    }
+
+    /// Same as set_charge_fet, but polls the FET drive state afterwards and
+    /// returns Error::FetDidNotSwitch if the charge FET did not reach the
+    /// requested state.
+    ///
+    /// Note that enabling can legitimately fail while a charging protection
+    /// fault is active.
+    pub fn set_charge_fet_confirmed(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.set_charge_fet(enable)?;
+        self.wait_for_fet_state(FProtStatCode::ChargeFetOn, enable)
+    }
+
+    /// Read whether the (charge, discharge) FET gates are currently driven on
+    pub fn read_fet_status(&mut self) -> Result<(bool, bool), Error<E>> {
+        let val = self.read_named_register(Register::FProtStat)?;
+        Ok((
+            has_code(FProtStatCode::ChargeFetOn as u16, val),
+            has_code(FProtStatCode::DischargeFetOn as u16, val),
+        ))
+    }
+
+    /// Read whether current is flowing through the body diode of an off FET.
+    ///
+    /// The MAX17320 has no status bit for this, so it is inferred from the FET
+    /// gate state in FProtStat and the direction of Current: current in the
+    /// direction an off FET would block can only pass through its body diode.
+    pub fn read_body_diode_conduction(&mut self) -> Result<BodyDiodeConduction, Error<E>> {
+        let (charge_on, discharge_on) = self.read_fet_status()?;
+        let current_ma = self.read_current()?;
+        Ok(body_diode_conduction(charge_on, discharge_on, current_ma))
+    }
+
+    /// Read the actual FET gate drive state together with the CHGOff/DISOff
+    /// software overrides, to tell a user-disabled FET from one held off by a fault
+    pub fn read_fet_drive_status(&mut self) -> Result<FetDriveStatus, Error<E>> {
+        let (charge_on, discharge_on) = self.read_fet_status()?;
+        let comm_stat = self.read_named_register(Register::CommStat)?;
+        Ok(FetDriveStatus {
+            charge_on,
+            discharge_on,
+            charge_commanded_off: has_code(CommStatCode::ChargeOff as u16, comm_stat),
+            discharge_commanded_off: has_code(CommStatCode::DischargeOff as u16, comm_stat),
+        })
+    }
+
+    /// Report why the discharge FET is off. A software override takes precedence
+    /// over protection faults, since the FET stays off while DISOff is set even
+    /// once the fault clears.
+    pub fn read_discharge_disabled_reason(&mut self) -> Result<DischargeDisabledReason, Error<E>> {
+        let fets = self.read_fet_drive_status()?;
+        if fets.discharge_on {
+            return Ok(DischargeDisabledReason::None);
+        }
+        if fets.discharge_commanded_off {
+            return Ok(DischargeDisabledReason::UserCommanded);
+        }
+        let status = self.read_protection_status()?;
+        Ok(discharge_fault_reason(status))
+    }
+
+    fn set_fet_override(&mut self, code: CommStatCode, off: bool) -> Result<(), Error<E>> {
+        let current = self.read_named_register(Register::CommStat)?;
+        let new = if off {
+            current | code as u16
+        } else {
+            current & !(code as u16)
+        };
+        let fet_bits = new & FET_OVERRIDE_BITS;
+        self.begin_write()?;
+        self.write_named_register(Register::CommStat, fet_bits)?;
+        self.end_write()
+    }
+
+    fn wait_for_fet_state(&mut self, fet: FProtStatCode, on: bool) -> Result<(), Error<E>> {
+        let fet = fet as u16;
+        for _ in 0..sequence::MAX_LOOP {
+            let val = self.read_named_register(Register::FProtStat)?;
+            if has_code(fet, val) == on {
+                return Ok(());
+            }
+        }
+        Err(Error::FetDidNotSwitch)
+    }
+
     /// Direct cell voltage measurement for Cell1 (in volts)
     pub fn read_cell1(&mut self) -> Result<f32, Error<E>> {
         let raw = self.read_named_register(Register::Cell1)?;
@@ -231,43 +1074,375 @@ where
     /// Read the total pack voltage measured inside the protector (V)
     pub fn read_batt(&mut self) -> Result<f32, Error<E>> {
         let raw = self.read_named_register(Register::Batt)?;
-        Ok(convert_to_voltage(raw))
+        Ok(convert_to_pack_voltage(raw))
+    }
+
+    /// Read the averaged total pack voltage (V).
+    ///
+    /// The MAX17320 has no averaged Batt register, so this is derived by summing
+    /// the AvgCell registers of the configured cells.
+    pub fn read_average_batt(&mut self) -> Result<f32, Error<E>> {
+        let n_cells = self.read_cell_count()?;
+        let regs = [
+            Register::AvgCell1,
+            Register::AvgCell2,
+            Register::AvgCell3,
+            Register::AvgCell4,
+        ];
+        let mut total = 0.0;
+        for reg in regs.into_iter().take(n_cells as usize) {
+            total += convert_to_voltage(self.read_named_register(reg)?);
+        }
+        Ok(total)
     }
 
     /// Read the voltage between PACK+ and GND (V)
     pub fn read_pckp(&mut self) -> Result<f32, Error<E>> {
         let raw = self.read_named_register(Register::Pckp)?;
-        Ok(convert_to_voltage(raw))
+        Ok(convert_to_pack_voltage(raw))
     }
 
-    /// Read permanent battery status information
+    /// Infer what is attached to the pack terminals from the BATT and PCKP
+    /// voltages and the current. There is no dedicated attach/detach status bit,
+    /// so an idle host reads as Disconnected.
+    pub fn read_pack_connection_state(&mut self) -> Result<PackConnection, Error<E>> {
+        let batt_v = self.read_batt()?;
+        let pckp_v = self.read_pckp()?;
+        let current_ma = self.read_current()?;
+        Ok(classify_pack_connection(batt_v, pckp_v, current_ma))
+    }
+
+    /// Read the voltage drop across the protection FETs (BATT - PCKP) and
+    /// estimate their combined on resistance from the current. Track the
+    /// resistance at a similar current over time; a rising value is an early
+    /// sign of FET degradation. The estimate is only meaningful while both FETs
+    /// are on.
+    pub fn read_fet_voltage_drop(&mut self) -> Result<FetVoltageDrop, Error<E>> {
+        let batt_v = self.read_batt()?;
+        let pckp_v = self.read_pckp()?;
+        let current_ma = self.read_current()?;
+        Ok(fet_voltage_drop(batt_v, pckp_v, current_ma))
+    }
+
+    /// Read the number of series cells configured in nPackCfg (2 to 4)
+    pub fn read_cell_count(&mut self) -> Result<u8, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        Ok((code & 0b11) as u8 + 2)
+    }
+
+    /// Direct cell voltage measurements for Cell1 to Cell4 (in volts).
+    ///
+    /// Cells above the configured cell count are not measured and should be ignored.
+    pub fn read_all_cells(&mut self) -> Result<[f32; 4], Error<E>> {
+        Ok([
+            self.read_cell1()?,
+            self.read_cell2()?,
+            self.read_cell3()?,
+            self.read_cell4()?,
+        ])
+    }
+
+    /// Check that the sum of the active cell voltages agrees with the pack voltage
+    /// measured by the protector (Batt) to within tolerance_v volts.
+    ///
+    /// A mismatch usually indicates a broken or disconnected cell sense line.
+    pub fn check_cell_sense_integrity(&mut self, tolerance_v: f32) -> Result<bool, Error<E>> {
+        let n_cells = self.read_cell_count()? as usize;
+        let cells = self.read_all_cells()?;
+        let batt = self.read_batt()?;
+        Ok(cells_agree_with_pack(&cells[..n_cells], batt, tolerance_v))
+    }
+
+    /// Read permanent battery status information, i.e. the permanent failure
+    /// latch in nBattStatus. The bit layout matches ProtAlertCode; 0 means no
+    /// permanent failure is recorded.
     pub fn read_battery_status(&mut self) -> Result<u16, Error<E>> {
         let val = self.read_named_register_nvm(RegisterNvm::NBattStatus)?;
         Ok(val)
     }
 
-    /// Unlock write protection
-    fn unlock_write_protection(&mut self) -> Result<(), Error<E>> {
-        self.write_named_register(Register::CommStat, 0x0000)?;
-        self.write_named_register(Register::CommStat, 0x0000)?;
+    /// Read the permanent failure latch. This is the same nBattStatus read as
+    /// read_battery_status, returned in ProtAlertCode layout; 0 means the pack
+    /// has never latched a permanent failure.
+    pub fn read_permanent_fail_latch(&mut self) -> Result<u16, Error<E>> {
+        self.read_battery_status()
+    }
+
+    /// Read the lifetime history of min/max conditions and protection faults
+    /// recorded in NVM, for reconstructing what happened to a returned pack.
+    pub fn read_protection_history(&mut self) -> Result<ProtectionHistory, Error<E>> {
+        let max_min_temp = self.read_named_register_nvm(RegisterNvm::NMaxMinTemp)?;
+        let max_min_volt = self.read_named_register_nvm(RegisterNvm::NMaxMinVolt)?;
+        let max_min_curr = self.read_named_register_nvm(RegisterNvm::NMaxMinCurr)?;
+        let fault_log = self.read_named_register_nvm(RegisterNvm::NFaultLog)?;
+        Ok(ProtectionHistory::from_registers(
+            max_min_temp,
+            max_min_volt,
+            max_min_curr,
+            fault_log,
+            self.r_sense,
+        ))
+    }
+
+    /// Read the pack identification fields programmed at manufacture
+    pub fn read_manufacturer_info(&mut self) -> Result<ManufacturerInfo, Error<E>> {
+        let mut name = [0; 3];
+        let mut serial_number = [0; 4];
+        self.read_nvm_table(RegisterNvm::NManfctrName0, &mut name)?;
+        self.read_nvm_table(RegisterNvm::NSerialNumber0, &mut serial_number)?;
+        let date = self.read_named_register_nvm(RegisterNvm::NManfctrDate)?;
+        Ok(ManufacturerInfo {
+            name,
+            date: ManufactureDate::from_bits(date),
+            serial_number,
+        })
+    }
+
+    /// Write the pack identification fields into the NVM shadow RAM.
+    /// Call commit_nonvolatile_memory afterwards to make them permanent.
+    pub fn write_manufacturer_info(&mut self, info: &ManufacturerInfo) -> Result<(), Error<E>> {
+        let date = info
+            .date
+            .to_bits()
+            .ok_or(Error::InvalidConfigurationValue(info.date.year))?;
+        self.begin_write()?;
+        self.write_nvm_table(RegisterNvm::NManfctrName0, &info.name)?;
+        self.write_named_register_nvm(RegisterNvm::NManfctrDate, date)?;
+        self.write_nvm_table(RegisterNvm::NSerialNumber0, &info.serial_number)?;
+        self.end_write()?;
         Ok(())
     }
 
-    fn lock_write_protection(&mut self) -> Result<(), Error<E>> {
-        self.write_named_register(Register::CommStat, 0x00F9)?;
-        self.write_named_register(Register::CommStat, 0x00F9)?;
+    /// Read the NVM restore and write-back configuration (nNVCfg0-2)
+    pub fn read_nv_config(&mut self) -> Result<NvConfig, Error<E>> {
+        Ok(NvConfig {
+            nv_cfg0: self.read_named_register_nvm(RegisterNvm::NNvCfg0)?,
+            nv_cfg1: self.read_named_register_nvm(RegisterNvm::NNvCfg1)?,
+            nv_cfg2: self.read_named_register_nvm(RegisterNvm::NNvCfg2)?,
+        })
+    }
+
+    /// Read which learned values the gauge saves to NVM by itself and how often.
+    /// Use NvConfig::disable_auto_save with write_nv_config to stop it.
+    pub fn read_auto_save_config(&mut self) -> Result<AutoSaveConfig, Error<E>> {
+        Ok(self.read_nv_config()?.into())
+    }
+
+    /// Write the NVM restore and write-back configuration (nNVCfg0-2)
+    pub fn write_nv_config(&mut self, config: NvConfig) -> Result<(), Error<E>> {
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NNvCfg0, config.nv_cfg0)?;
+        self.write_named_register_nvm(RegisterNvm::NNvCfg1, config.nv_cfg1)?;
+        self.write_named_register_nvm(RegisterNvm::NNvCfg2, config.nv_cfg2)?;
+        self.end_write()?;
         Ok(())
     }
 
-    /// Read the pack configuration
-    pub fn read_pack_config(&mut self) -> Result<u16, Error<E>> {
-        let val = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
-        Ok(val)
+    /// Snapshot the whole nonvolatile shadow RAM (0x180-0x1EF) for backup or
+    /// cloning to another pack. `out[i]` holds register 0x180 + i.
+    pub fn backup_nonvolatile_memory(
+        &mut self,
+        out: &mut [u16; NVM_SHADOW_LEN],
+    ) -> Result<(), Error<E>> {
+        let mut image = [0; NVM_SHADOW_LEN];
+        for (i, chunk) in image.chunks_mut(i2c_interface::MAX_BLOCK_WORDS).enumerate() {
+            let reg = NVM_SHADOW_START + (i * i2c_interface::MAX_BLOCK_WORDS) as u8;
+            self.read_block(reg, self.address_nvm, chunk)?;
+        }
+        *out = image;
+        Ok(())
     }
 
-    /// Set the pack configuration according to application schematic.
+    /// Restore an NVM shadow image taken with backup_nonvolatile_memory and
+    /// commit it.
     ///
-    /// n_cells: number of cells, min 2, max 4.
+    /// Only the user configuration, 0x180-0x1DF, is restored; the read-only
+    /// serial number, ROM ID and update counter at 0x1E0-0x1EF are skipped.
+    /// Error::NvmWritesExhausted is returned before anything is written if no NVM
+    /// write cycles remain. Every register is then written and read back before
+    /// anything is committed; the first mismatch is returned as
+    /// Error::NvmVerifyFailed(address) and nothing is committed. A mismatched
+    /// image can brick a pack, so only restore images taken from the same pack
+    /// design.
+    pub fn restore_nonvolatile_memory(
+        &mut self,
+        image: &[u16; NVM_SHADOW_LEN],
+    ) -> Result<(), Error<E>> {
+        if self.read_remaining_nvm_writes()? == 0 {
+            return Err(Error::NvmWritesExhausted);
+        }
+        let image = &image[..NVM_WRITABLE_LEN];
+        self.configure(|bat| {
+            for (i, code) in image.iter().enumerate() {
+                bat.write_nvm_address(NVM_SHADOW_START + i as u8, *code)?;
+            }
+            for (i, code) in image.iter().enumerate() {
+                let reg = NVM_SHADOW_START + i as u8;
+                if bat.read_nvm_address(reg)? != *code {
+                    return Err(Error::NvmVerifyFailed(0x100 | reg as u16));
+                }
+            }
+            bat.send_nvm_command(CommandCode::CopyNvBlock, None)
+        })
+    }
+
+    /// Read how many NVM write cycles (out of 7) are left
+    pub fn read_remaining_nvm_writes(&mut self) -> Result<u8, Error<E>> {
+        self.send_nvm_command(
+            CommandCode::RecallRemainingUpdates,
+            Some(RegisterNvm::NRemainingUpdates),
+        )?;
+        let code = self.read_named_register_nvm(RegisterNvm::NRemainingUpdates)?;
+        Ok(remaining_nvm_writes(code))
+    }
+
+    /// Copy the nonvolatile shadow RAM into NVM.
+    ///
+    /// Each call consumes one of the limited NVM write cycles (7 over the life of
+    /// the part), so batch all configuration changes before committing.
+    pub fn commit_nonvolatile_memory(&mut self) -> Result<(), Error<E>> {
+        self.configure(|bat| bat.send_nvm_command(CommandCode::CopyNvBlock, None))
+    }
+
+    /// Whether this pack has ever detected internal self-discharge (leakage).
+    ///
+    /// Backed by the LeakageDetectionFault bit (bit 0, ProtAlertCode layout) of the
+    /// nonvolatile fault log nFaultLog, and of the nBattStatus permanent failure
+    /// latch, neither of which is affected by clearing ProtAlrt. nFaultLog is
+    /// written back by the gauge's automatic NVM saves, so a leak seen since the
+    /// last save is only in ProtAlrt until then.
+    pub fn read_ever_leaked(&mut self) -> Result<bool, Error<E>> {
+        let leak = ProtAlertCode::LeakageDetectionFault as u16;
+        let fault_log = self.read_named_register_nvm(RegisterNvm::NFaultLog)?;
+        let latch = self.read_battery_status()?;
+        Ok(has_code(leak, fault_log | latch))
+    }
+
+    /// Clear the permanent failure latch.
+    ///
+    /// DANGER: this is intended only for qualification packs that were tripped
+    /// deliberately. It clears nBattStatus and commits the shadow RAM to NVM,
+    /// consuming one of the limited NVM write cycles. Clearing the latch does not
+    /// fix the underlying failure, so find and remove the cause first.
+    #[cfg(feature = "permanent-fail-clear")]
+    ///
+    /// A rejected NVM commit is reported as `Error::NonvolatileError` for
+    /// nBattStatus, since the latch is the register that failed to clear. Write
+    /// protection is restored whether or not the clear succeeds.
+    pub fn clear_permanent_fail_latch(&mut self) -> Result<(), Error<E>> {
+        self.configure(|bat| {
+            bat.write_named_register_nvm(RegisterNvm::NBattStatus, 0x0000)?;
+            bat.send_nvm_command(CommandCode::CopyNvBlock, Some(RegisterNvm::NBattStatus))
+        })
+    }
+
+    /// Read whether any write protection is enabled, i.e. whether the global
+    /// write protection bit or any of WP1-WP5 are set in CommStat.
+    pub fn is_write_protected(&mut self) -> Result<bool, Error<E>> {
+        let val = self.read_named_register(Register::CommStat)?;
+        Ok(has_code(WRITE_PROTECTION_BITS, val))
+    }
+
+    /// Write a nonvolatile shadow register, first checking that write protection
+    /// is off. The chip ignores writes while protected, so this returns
+    /// Error::WriteProtected instead of silently leaving the value unchanged.
+    /// Call it inside configure, or after unlock_write_protection.
+    pub fn write_nvm_checked(&mut self, reg: RegisterNvm, code: u16) -> Result<(), Error<E>> {
+        if self.is_write_protected()? {
+            return Err(Error::WriteProtected);
+        }
+        self.write_named_register_nvm(reg, code)
+    }
+
+    /// Read which register pages are currently write protected
+    pub fn read_write_protection(&mut self) -> Result<WriteProtectionPages, Error<E>> {
+        let val = self.read_named_register(Register::CommStat)?;
+        Ok(WriteProtectionPages::from_bits(val))
+    }
+
+    /// Write protect exactly the given register pages, leaving the rest writable.
+    /// The CHGOff/DISOff FET overrides in CommStat are preserved.
+    ///
+    /// The pages are remembered: setters and configure relock to this set rather
+    /// than to every page. Inside configure the pages take effect when it relocks.
+    pub fn set_write_protection(&mut self, pages: WriteProtectionPages) -> Result<(), Error<E>> {
+        self.protected_pages = pages;
+        if self.write_unlocked {
+            return Ok(());
+        }
+        self.lock_write_protection()
+    }
+
+    /// Run several configuration writes inside one unlock window. Setters called
+    /// from f do not unlock and relock on their own, so configuring many NVM
+    /// parameters costs one unlock and one lock. Write protection is restored
+    /// even if f fails, and f's error is returned.
+    pub fn configure<T, F>(&mut self, f: F) -> Result<T, Error<E>>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error<E>>,
+    {
+        if self.write_unlocked {
+            return f(self);
+        }
+        self.unlock_write_protection()?;
+        self.write_unlocked = true;
+        let result = f(self);
+        self.write_unlocked = false;
+        let locked = self.lock_write_protection();
+        let value = result?;
+        locked?;
+        Ok(value)
+    }
+
+    /// Unlock for a setter, unless an enclosing configure already has
+    fn begin_write(&mut self) -> Result<(), Error<E>> {
+        if self.write_unlocked {
+            return Ok(());
+        }
+        self.unlock_write_protection()
+    }
+
+    /// Relock after a setter, unless an enclosing configure will
+    fn end_write(&mut self) -> Result<(), Error<E>> {
+        if self.write_unlocked {
+            return Ok(());
+        }
+        self.lock_write_protection()
+    }
+
+    /// Unlock write protection.
+    ///
+    /// CommStat also holds the CHGOff/DISOff FET overrides, so they are read
+    /// first and written back; unlocking never turns a FET that was commanded
+    /// off back on.
+    pub fn unlock_write_protection(&mut self) -> Result<(), Error<E>> {
+        let fet_bits = self.read_named_register(Register::CommStat)? & FET_OVERRIDE_BITS;
+        self.write_named_register(Register::CommStat, fet_bits)?;
+        self.write_named_register(Register::CommStat, fet_bits)?;
+        Ok(())
+    }
+
+    /// Lock write protection, protecting the pages last given to
+    /// set_write_protection (every page by default). The CHGOff/DISOff FET
+    /// overrides are preserved.
+    pub fn lock_write_protection(&mut self) -> Result<(), Error<E>> {
+        let fet_bits = self.read_named_register(Register::CommStat)? & FET_OVERRIDE_BITS;
+        let code = fet_bits | self.protected_pages.to_bits();
+        self.write_named_register(Register::CommStat, code)?;
+        self.write_named_register(Register::CommStat, code)?;
+        Ok(())
+    }
+
+    /// Read the pack configuration
+    pub fn read_pack_config(&mut self) -> Result<u16, Error<E>> {
+        let val = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        Ok(val)
+    }
+
+    /// Set the pack configuration according to application schematic.
+    ///
+    /// n_cells: number of cells, min 2, max 4. The MAX17320 only supports 2S-4S
+    /// packs, other counts return Error::UnsupportedCellCount.
     ///
     /// n_therms: number of thermistor channels to enable (not including the die thermistor), min 0, max 4.
     ///
@@ -287,24 +1462,576 @@ where
         always_on_regulator_config: AlwaysOnRegulatorConfiguration,
         battery_pack_update: BatteryPackUpdate,
     ) -> Result<(), Error<E>> {
-        if n_cells < 2 || n_cells > 4 {
-            return Err(Error::InvalidConfigurationValue(n_cells as u16));
+        let code = encode_pack_config(
+            n_cells,
+            n_therms,
+            therm_type,
+            charge_pump_voltage_config,
+            always_on_regulator_config,
+            battery_pack_update,
+        )?;
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NPackCfg, code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the role of each thermistor channel TH1-TH4
+    pub fn read_thermistor_channel_config(&mut self) -> Result<[ThermChannelRole; 4], Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        let enabled = ((code & PACK_CFG_N_THERMS_MASK) >> 2) as usize;
+        let mut channels = [ThermChannelRole::Disabled; 4];
+        for channel in channels.iter_mut().take(enabled) {
+            *channel = ThermChannelRole::Protection;
         }
-        let n_cells = n_cells - 2;
-        if n_therms > 4 {
-            return Err(Error::InvalidConfigurationValue(n_therms as u16));
+        Ok(channels)
+    }
+
+    /// Set which thermistor channels TH1-TH4 are used, updating nPackCfg.NThrms.
+    ///
+    /// The MAX17320 enables channels by count starting from TH1, so the enabled
+    /// channels must be a prefix (e.g. TH1 and TH2 for two thermistors); a gap
+    /// returns Error::InvalidConfigurationValue with the number (1-4) of the
+    /// first channel that cannot be expressed.
+    pub fn set_thermistor_channel_config(
+        &mut self,
+        channels: [ThermChannelRole; 4],
+    ) -> Result<(), Error<E>> {
+        let enabled = channels
+            .iter()
+            .take_while(|role| **role == ThermChannelRole::Protection)
+            .count();
+        if let Some(i) = channels[enabled..]
+            .iter()
+            .position(|role| *role != ThermChannelRole::Disabled)
+        {
+            return Err(Error::InvalidConfigurationValue((enabled + i + 1) as u16));
         }
-        let n_therms = n_therms << 2;
+        let current = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        let code = (current & !PACK_CFG_N_THERMS_MASK) | (enabled as u16) << 2;
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NPackCfg, code)?;
+        self.end_write()?;
+        Ok(())
+    }
 
-        let code = n_cells as u16
-            | n_therms as u16
-            | therm_type as u16
-            | charge_pump_voltage_config as u16
-            | always_on_regulator_config as u16
-            | battery_pack_update as u16;
-        self.unlock_write_protection()?;
+    /// Read the protection FET gate drive configuration.
+    ///
+    /// The MAX17320 only drives external, high side, N-channel CHG and DIS FETs
+    /// through its charge pump; there is no internal FET mode and no polarity
+    /// setting, so the only configurable part is the gate drive voltage.
+    /// Returns Error::InvalidConfigurationValue with nPackCfg if CPCfg holds the
+    /// reserved code.
+    pub fn read_fet_configuration(&mut self) -> Result<FetConfiguration, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        FetConfiguration::from_bits(code).ok_or(Error::InvalidConfigurationValue(code))
+    }
+
+    /// Set the protection FET gate drive voltage, leaving the rest of nPackCfg unchanged
+    pub fn set_fet_configuration(&mut self, config: FetConfiguration) -> Result<(), Error<E>> {
+        let current = self.read_named_register_nvm(RegisterNvm::NPackCfg)?;
+        let code = (current & !FetConfiguration::MASK) | config.charge_pump as u16;
+        self.begin_write()?;
         self.write_named_register_nvm(RegisterNvm::NPackCfg, code)?;
-        self.lock_write_protection()?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the thermistor curve coefficients
+    pub fn read_thermistor_config(&mut self) -> Result<ThermistorConfig, Error<E>> {
+        Ok(ThermistorConfig {
+            gain: self.read_named_register_nvm(RegisterNvm::NTGain)?,
+            offset: self.read_named_register_nvm(RegisterNvm::NTOff)?,
+            curve: self.read_named_register_nvm(RegisterNvm::NTCurve)?,
+        })
+    }
+
+    /// Set the thermistor curve coefficients. The thermistor type and number of
+    /// thermistors are set separately with set_pack_config.
+    pub fn set_thermistor_config(&mut self, cfg: ThermistorConfig) -> Result<(), Error<E>> {
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NTGain, cfg.gain)?;
+        self.write_named_register_nvm(RegisterNvm::NTOff, cfg.offset)?;
+        self.write_named_register_nvm(RegisterNvm::NTCurve, cfg.curve)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Apply a default configuration for a standard 2S Li-ion pack, see configure_standard_liion_3s
+    pub fn configure_standard_liion_2s(&mut self, capacity_mah: f32) -> Result<(), Error<E>> {
+        self.configure_standard_liion(2, capacity_mah)
+    }
+
+    /// Apply a default configuration for a standard 3S Li-ion pack of capacity_mah:
+    /// - one 10kΩ NTC thermistor, 8V charge pump, ALDO disabled
+    /// - standard Li-ion cell model charged to 4.2V
+    /// - cell voltage alerts at 3.0V and 4.2V
+    /// - empty at 3.0V per cell, recovering at 3.88V (VEmpty)
+    ///
+    /// Use the individual setters afterwards for anything that differs.
+    pub fn configure_standard_liion_3s(&mut self, capacity_mah: f32) -> Result<(), Error<E>> {
+        self.configure_standard_liion(3, capacity_mah)
+    }
+
+    /// Apply a default configuration for a standard 4S Li-ion pack, see configure_standard_liion_3s
+    pub fn configure_standard_liion_4s(&mut self, capacity_mah: f32) -> Result<(), Error<E>> {
+        self.configure_standard_liion(4, capacity_mah)
+    }
+
+    fn configure_standard_liion(&mut self, n_cells: u8, capacity_mah: f32) -> Result<(), Error<E>> {
+        self.configure(|bat| {
+            bat.set_pack_config(
+                n_cells,
+                1,
+                ThermistorType::Ntc10KOhm,
+                ChargePumpVoltageConfiguration::Cp8V,
+                AlwaysOnRegulatorConfiguration::Disabled,
+                BatteryPackUpdate::UpdateEvery22p4s,
+            )?;
+            bat.set_voltage_alert_threshold(3.0, 4.2)?;
+            bat.set_empty_voltage(3.0, 3.88)?;
+            // set_cell_model's refresh also picks up the new design capacity
+            bat.write_design_capacity(capacity_mah)?;
+            bat.set_cell_model(
+                CellModel::LithiumCobalt,
+                ChargeVoltageClass::UpTo4p275V,
+                ThermistorType::Ntc10KOhm,
+            )
+        })
+    }
+
+    /// Read the optional protection feature enables (nProtCfg2)
+    pub fn read_protection_config2(&mut self) -> Result<ProtectionConfig2, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NProtCfg2)?;
+        Ok(ProtectionConfig2::from_bits(code))
+    }
+
+    /// Set the optional protection feature enables (nProtCfg2).
+    /// Bits outside ProtectionConfig2::MASK are preserved.
+    pub fn set_protection_config2(&mut self, config: ProtectionConfig2) -> Result<(), Error<E>> {
+        let current = self.read_named_register_nvm(RegisterNvm::NProtCfg2)?;
+        let code = (current & !ProtectionConfig2::MASK) | config.to_bits();
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NProtCfg2, code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the AvgCurrent, AvgVCell and AvgTA averaging time constants
+    pub fn read_filter_config(&mut self) -> Result<FilterConfig, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NFilterCfg)?;
+        Ok(FilterConfig::from_bits(code))
+    }
+
+    /// Set the averaging time constants. Each is rounded to the nearest step the
+    /// gauge supports (powers of two of 45s, see FilterConfig for the ranges).
+    /// The mixing and empty compensation fields of nFilterCfg are preserved.
+    pub fn set_filter_config(
+        &mut self,
+        current_tc: Duration,
+        voltage_tc: Duration,
+        temp_tc: Duration,
+    ) -> Result<(), Error<E>> {
+        let config = FilterConfig {
+            current: current_tc,
+            voltage: voltage_tc,
+            temperature: temp_tc,
+        };
+        let bits = config
+            .encode_checked()
+            .map_err(Error::InvalidConfigurationValue)?;
+        let current = self.read_named_register_nvm(RegisterNvm::NFilterCfg)?;
+        let code = (current & !FilterConfig::MASK) | bits;
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NFilterCfg, code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the measurement period used in hibernate mode (HibCfg.HibScalar).
+    ///
+    /// In active mode the gauge always measures once per 351.5ms task period;
+    /// only the hibernate cadence, 351.5ms × 2^HibScalar, is configurable.
+    pub fn read_measurement_period(&mut self) -> Result<Duration, Error<E>> {
+        let code = self.read_named_register(Register::HibCfg)?;
+        Ok(hibernate_period(code & HIB_SCALAR_MASK))
+    }
+
+    /// Set the hibernate mode measurement period, rounded to the nearest step
+    /// (351.5ms to 45s in powers of two). A period of zero or beyond twice the
+    /// slowest step returns Error::InvalidConfigurationValue. The other HibCfg
+    /// fields are preserved.
+    ///
+    /// Like set_hibernate_config this writes the live HibCfg register, so the
+    /// period reverts to the nHibCfg value after a reset unless that is
+    /// committed to NVM too.
+    pub fn set_measurement_period(&mut self, period: Duration) -> Result<(), Error<E>> {
+        let scalar = hib_scalar_for(period)
+            .ok_or(Error::InvalidConfigurationValue(period.as_millis() as u16))?;
+        let current = self.read_named_register(Register::HibCfg)?;
+        self.begin_write()?;
+        self.write_named_register(Register::HibCfg, (current & !HIB_SCALAR_MASK) | scalar)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the protection trip and debounce delays (nDelayCfg)
+    pub fn read_protection_timers(&mut self) -> Result<ProtectionTimers, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NDelayCfg)?;
+        Ok(ProtectionTimers::from_bits(code))
+    }
+
+    /// Read the charge communication watchdog timeout, or None if the watchdog
+    /// is disabled (nProtCfg.ChgWDTEn clear)
+    pub fn read_charge_watchdog_timeout(&mut self) -> Result<Option<Duration>, Error<E>> {
+        let prot_cfg = self.read_named_register_nvm(RegisterNvm::NProtCfg)?;
+        if prot_cfg & CHARGE_WATCHDOG_ENABLE == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.read_protection_timers()?.charge_watchdog))
+    }
+
+    /// Set the charge communication watchdog timeout, or disable it with None.
+    /// While charging the host must talk to the gauge within this time or
+    /// ProtStatus.ChgWDT trips and the charge FET turns off. The chip supports
+    /// 15, 30, 60 and 120 minutes; anything else returns
+    /// Error::InvalidConfigurationValue(minutes) without writing.
+    pub fn set_charge_watchdog_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error<E>> {
+        let bits = match timeout {
+            Some(t) => Some(
+                ProtectionTimers::charge_watchdog_bits(t)
+                    .ok_or(Error::InvalidConfigurationValue((t.as_secs() / 60) as u16))?,
+            ),
+            None => None,
+        };
+        let prot_cfg = self.read_named_register_nvm(RegisterNvm::NProtCfg)?;
+        let delay_cfg = self.read_named_register_nvm(RegisterNvm::NDelayCfg)?;
+        self.begin_write()?;
+        match bits {
+            Some(bits) => {
+                self.write_named_register_nvm(
+                    RegisterNvm::NDelayCfg,
+                    (delay_cfg & !ProtectionTimers::CHARGE_WATCHDOG_MASK) | bits,
+                )?;
+                self.write_named_register_nvm(
+                    RegisterNvm::NProtCfg,
+                    prot_cfg | CHARGE_WATCHDOG_ENABLE,
+                )?;
+            }
+            None => self.write_named_register_nvm(
+                RegisterNvm::NProtCfg,
+                prot_cfg & !CHARGE_WATCHDOG_ENABLE,
+            )?,
+        }
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Set the overvoltage/undervoltage protection mode.
+    ///
+    /// The MAX17320 only protects per cell: nOVPrtTh and nUVPrtTh are per cell
+    /// thresholds compared against the highest and lowest cell, and there is no
+    /// register bit to switch to the pack total. So there is nothing to read back
+    /// or write; PerCell is accepted as is, and PackTotal returns
+    /// Error::UnsupportedProtectionVoltageMode rather than silently protecting
+    /// per cell.
+    pub fn set_protection_voltage_mode(
+        &mut self,
+        mode: ProtectionVoltageMode,
+    ) -> Result<(), Error<E>> {
+        match mode {
+            ProtectionVoltageMode::PerCell => Ok(()),
+            ProtectionVoltageMode::PackTotal => Err(Error::UnsupportedProtectionVoltageMode),
+        }
+    }
+
+    /// Read the voltage floor of the gauge's model (VEmpty) and the protector's
+    /// undervoltage lockout. Below empty_v the SOC is pinned at 0% and no longer
+    /// tracks the pack; below lockout_v the pack is disconnected.
+    pub fn read_operating_voltage_limits(&mut self) -> Result<OperatingVoltageLimits, Error<E>> {
+        let vempty = self.read_named_register(Register::VEmpty)?;
+        let (lockout_v, _) = self.read_undervoltage_threshold()?;
+        Ok(OperatingVoltageLimits {
+            empty_v: (vempty >> 7) as f32 * VEMPTY_LSB_V,
+            recovery_v: (vempty & 0x7F) as f32 * VEMPTY_RECOVERY_LSB_V,
+            lockout_v,
+        })
+    }
+
+    /// Set the per cell voltage the gauge treats as empty (0 to 5.11V in 10mV
+    /// steps) and the voltage it must recover to before leaving the empty state
+    /// (0 to 5.08V in 40mV steps). Set this before the cell model is loaded, as
+    /// the model refresh uses it.
+    pub fn set_empty_voltage(&mut self, empty_v: f32, recovery_v: f32) -> Result<(), Error<E>> {
+        let empty = empty_v / VEMPTY_LSB_V + 0.5;
+        if !(0.0..512.0).contains(&empty) {
+            return Err(Error::InvalidConfigurationValue((empty_v * 1000.0) as u16));
+        }
+        let recovery = recovery_v / VEMPTY_RECOVERY_LSB_V + 0.5;
+        if !(0.0..128.0).contains(&recovery) {
+            return Err(Error::InvalidConfigurationValue(
+                (recovery_v * 1000.0) as u16,
+            ));
+        }
+        self.begin_write()?;
+        self.write_named_register(Register::VEmpty, (empty as u16) << 7 | recovery as u16)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the undervoltage protection thresholds, returns tuple of
+    /// (trip_v, recovery_hysteresis_v) per cell
+    pub fn read_undervoltage_threshold(&mut self) -> Result<(f32, f32), Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NUvPrtTh)?;
+        Ok(decode_undervoltage_threshold(code))
+    }
+
+    /// Read the active protection faults and, for undervoltage, whether the
+    /// lowest active cell has recovered above trip + hysteresis yet. Other faults
+    /// are only reported as active; their recovery thresholds are not decoded.
+    pub fn read_recovery_status(&mut self) -> Result<RecoveryStatus, Error<E>> {
+        let active_faults = self.read_protection_status()?;
+        let undervoltage = if has_code(ProtStatusCode::Undervoltage as u16, active_faults) {
+            let (trip_v, hysteresis_v) = self.read_undervoltage_threshold()?;
+            let n_cells = self.read_cell_count()? as usize;
+            let cells = self.read_all_cells()?;
+            let cell_v = cells[..n_cells].iter().copied().fold(f32::MAX, f32::min);
+            let recovery_v = trip_v + hysteresis_v;
+            Some(VoltageRecovery {
+                cell_v,
+                recovery_v,
+                met: cell_v >= recovery_v,
+            })
+        } else {
+            None
+        };
+        Ok(RecoveryStatus {
+            active_faults,
+            undervoltage,
+        })
+    }
+
+    /// Set the per cell undervoltage protection trip voltage (2.0V to 4.55V in
+    /// 10mV steps) and the hysteresis above it at which the discharge FET is
+    /// re-enabled (0 to 300mV in 20mV steps).
+    ///
+    /// Use at least 40mV of hysteresis (MIN_UNDERVOLTAGE_HYSTERESIS_V); the cell
+    /// voltage rebounds as soon as the load is removed and a smaller window makes
+    /// the protection chatter on and off around the threshold.
+    pub fn set_undervoltage_threshold(
+        &mut self,
+        trip_v: f32,
+        recovery_hysteresis_v: f32,
+    ) -> Result<(), Error<E>> {
+        let current = self.read_named_register_nvm(RegisterNvm::NUvPrtTh)?;
+        let code = encode_undervoltage_threshold(trip_v, recovery_hysteresis_v)
+            .map_err(Error::InvalidConfigurationValue)?;
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NUvPrtTh, (current & 0x000F) | code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Select the ModelGauge m5 cell model and reload it.
+    ///
+    /// LiFePO4 cells need the LithiumIronPhosphate model to report a sensible SOC.
+    /// The ModelCfg.Refresh bit is set and polled until the gauge clears it,
+    /// returning Error::Timeout if it never does.
+    pub fn set_cell_model(
+        &mut self,
+        model: CellModel,
+        charge_voltage: ChargeVoltageClass,
+        therm_type: ThermistorType,
+    ) -> Result<(), Error<E>> {
+        let r100 = match therm_type {
+            ThermistorType::Ntc10KOhm => 0,
+            ThermistorType::Ntc100KOhm => ModelCfgCode::R100 as u16,
+        };
+        let code = ModelCfgCode::Refresh as u16 | r100 | charge_voltage as u16 | model as u16;
+        self.begin_write()?;
+        self.write_named_register(Register::ModelCfg, code)?;
+        self.end_write()?;
+        self.wait_for_model_refresh()
+    }
+
+    /// Read the learned RCOMP0 model parameter, the characterized cell
+    /// resistance at 25°C. The value is unitless and characterization specific.
+    pub fn read_rcomp0(&mut self) -> Result<u16, Error<E>> {
+        let val = self.read_named_register(Register::RComp0)?;
+        Ok(val)
+    }
+
+    /// Override the RCOMP0 model parameter, e.g. with an offline characterization
+    pub fn set_rcomp0(&mut self, rcomp0: u16) -> Result<(), Error<E>> {
+        self.begin_write()?;
+        self.write_named_register(Register::RComp0, rcomp0)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the learned temperature compensation of RCOMP0, returns tuple of
+    /// (tempco_hot, tempco_cold) which apply above and below 25°C respectively
+    pub fn read_tempco(&mut self) -> Result<(u8, u8), Error<E>> {
+        let code = self.read_named_register(Register::TempCo)?;
+        let raw = code.to_be_bytes();
+        Ok((
+            raw[0], // Hot
+            raw[1], // Cold
+        ))
+    }
+
+    /// Override the temperature compensation of RCOMP0
+    pub fn set_tempco(&mut self, tempco_hot: u8, tempco_cold: u8) -> Result<(), Error<E>> {
+        let code = u16::from_be_bytes([tempco_hot, tempco_cold]);
+        self.begin_write()?;
+        self.write_named_register(Register::TempCo, code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the design capacity of the pack (mAh)
+    pub fn read_design_capacity(&mut self) -> Result<f32, Error<E>> {
+        let raw = self.read_named_register_nvm(RegisterNvm::NDesignCap)?;
+        Ok(convert_to_capacity(raw, self.r_sense))
+    }
+
+    /// Read the nominal pack voltage, capacity and energy from the configured
+    /// cell count and design capacity, using NOMINAL_CELL_VOLTAGE_V per cell
+    pub fn read_pack_specifications(&mut self) -> Result<PackSpec, Error<E>> {
+        let series_cells = self.read_cell_count()?;
+        let capacity_mah = self.read_design_capacity()?;
+        let nominal_voltage = series_cells as f32 * NOMINAL_CELL_VOLTAGE_V;
+        Ok(PackSpec {
+            series_cells,
+            nominal_voltage,
+            capacity_mah,
+            energy_wh: nominal_voltage * capacity_mah / 1000.0,
+        })
+    }
+
+    /// Read the capacity fade (%): how far the learned full capacity (FullCapRep)
+    /// has dropped below the design capacity (nDesignCap). A fresh pack that
+    /// learns more than its design capacity reads 0%.
+    pub fn read_capacity_fade_percent(&mut self) -> Result<f32, Error<E>> {
+        let design = self.read_design_capacity()?;
+        if design <= 0.0 {
+            return Err(Error::InvalidConfigurationValue(0));
+        }
+        let full = self.read_full_capacity()?;
+        Ok(capacity_fade_percent(full, design))
+    }
+
+    /// Set the design capacity of the pack (mAh) and reload the model so the
+    /// gauge uses it immediately.
+    pub fn set_design_capacity(&mut self, capacity_mah: f32) -> Result<(), Error<E>> {
+        self.write_design_capacity(capacity_mah)?;
+        self.refresh_model()
+    }
+
+    /// Write nDesignCap without reloading the model
+    fn write_design_capacity(&mut self, capacity_mah: f32) -> Result<(), Error<E>> {
+        let raw = capacity_mah * self.r_sense / CAPACITY_LSB_UVH;
+        if !(0.0..=u16::MAX as f32).contains(&raw) {
+            return Err(Error::InvalidConfigurationValue(capacity_mah as u16));
+        }
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NDesignCap, raw as u16)?;
+        self.end_write()
+    }
+
+    /// Set ModelCfg.Refresh so the gauge reprocesses model related configuration,
+    /// and wait for it to finish.
+    fn refresh_model(&mut self) -> Result<(), Error<E>> {
+        let code = self.read_named_register(Register::ModelCfg)?;
+        self.begin_write()?;
+        self.write_named_register(Register::ModelCfg, code | ModelCfgCode::Refresh as u16)?;
+        self.end_write()?;
+        self.wait_for_model_refresh()
+    }
+
+    /// Poll until the gauge clears ModelCfg.Refresh, or Error::Timeout
+    fn wait_for_model_refresh(&mut self) -> Result<(), Error<E>> {
+        let mut sequence = ModelRefreshSequence::new();
+        self.run_sequence(|read| sequence.step(read))
+    }
+
+    /// Configure hibernate mode in physical units.
+    ///
+    /// The HibCfg threshold is encoded as FullCapRep / 0.8h / 2^HibThreshold, so the
+    /// largest encodable threshold not above entry_current_ma is used. entry_time is
+    /// rounded up to the next 2.812s x 2^HibEnterTime step, max 360s.
+    /// The hibernate exit time and task period fields are left unchanged.
+    pub fn set_hibernate_config(
+        &mut self,
+        enable: bool,
+        entry_current_ma: f32,
+        entry_time: Duration,
+    ) -> Result<(), Error<E>> {
+        let full_cap = self.read_full_capacity()?;
+        let threshold = encode_hib_threshold(full_cap, entry_current_ma)
+            .ok_or(Error::InvalidConfigurationValue(entry_current_ma as u16))?;
+        let enter_time = encode_hib_enter_time(entry_time)
+            .ok_or(Error::InvalidConfigurationValue(entry_time.as_secs() as u16))?;
+        let current = self.read_named_register(Register::HibCfg)?;
+        let code = (enable as u16) << 15
+            | (enter_time as u16) << 12
+            | (threshold as u16) << 8
+            | (current & 0x00FF);
+        self.begin_write()?;
+        self.write_named_register(Register::HibCfg, code)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the hibernate mode configuration in physical units
+    pub fn read_hibernate_config(&mut self) -> Result<HibernateConfig, Error<E>> {
+        let full_cap = self.read_full_capacity()?;
+        let code = self.read_named_register(Register::HibCfg)?;
+        let threshold = (code >> 8) & 0x0F;
+        let enter_time = (code >> 12) & 0x07;
+        Ok(HibernateConfig {
+            enabled: code & (1 << 15) != 0,
+            entry_current_ma: full_cap / 0.8 / (1u32 << threshold) as f32,
+            entry_time: Duration::from_micros(HIB_ENTER_TIME_LSB_US << enter_time),
+        })
+    }
+
+    /// Read the custom cell model OCV table (nOCVTable0-11)
+    pub fn read_ocv_table(&mut self) -> Result<[u16; OCV_TABLE_LEN], Error<E>> {
+        let mut table = [0; OCV_TABLE_LEN];
+        self.read_nvm_table(RegisterNvm::NOcvTable0, &mut table)?;
+        Ok(table)
+    }
+
+    /// Write the custom cell model OCV table (nOCVTable0-11) from a cell
+    /// characterization, unlocking write protection around the write.
+    ///
+    /// Only the shadow RAM is written; the table is lost on reset unless the
+    /// NVM is committed afterwards.
+    pub fn write_ocv_table(&mut self, table: &[u16; OCV_TABLE_LEN]) -> Result<(), Error<E>> {
+        self.begin_write()?;
+        self.write_nvm_table(RegisterNvm::NOcvTable0, table)?;
+        self.end_write()?;
+        Ok(())
+    }
+
+    /// Read the time to full CV taper configuration
+    pub fn read_time_to_full_config(&mut self) -> Result<TimeToFullConfig, Error<E>> {
+        let code = self.read_named_register_nvm(RegisterNvm::NTtfCfg)?;
+        Ok(TimeToFullConfig::from_bits(code))
+    }
+
+    /// Set the time to full CV taper configuration to match the charger
+    pub fn set_time_to_full_config(&mut self, config: TimeToFullConfig) -> Result<(), Error<E>> {
+        let code = config
+            .encode_checked()
+            .map_err(Error::InvalidConfigurationValue)?;
+        self.begin_write()?;
+        self.write_named_register_nvm(RegisterNvm::NTtfCfg, code)?;
+        self.end_write()?;
         Ok(())
     }
 
@@ -319,6 +2046,9 @@ where
     /// disabled. Note that if this bit is set to 1, the ALSH bit will be set to
     /// 0 to prevent an alert condition from causing the device to enter shutdown mode.
     /// If this bit is set to 0, the ALSH bit is not changed.
+    ///
+    /// nConfig is only written when ALSH actually needs clearing, and the Config
+    /// write is verified by readback (Error::WriteVerifyFailed).
     pub fn set_alert_output_enable(&mut self, enable: bool) -> Result<(), Error<E>> {
         let current_config = self.read_named_register(Register::Config)?;
         let new_config: u16;
@@ -329,11 +2059,44 @@ where
             new_config = clear_bit(current_config, 2);
         }
         self.write_named_register(Register::Config, new_config)?;
+        if self.read_named_register(Register::Config)? != new_config {
+            return Err(Error::WriteVerifyFailed(Register::Config as u8));
+        }
+        Ok(())
+    }
+
+    /// Set the ALRT pin polarity (Config.ALRTp). Default = active low.
+    ///
+    /// The Config write is verified by readback (Error::WriteVerifyFailed).
+    pub fn set_alert_polarity(&mut self, active_high: bool) -> Result<(), Error<E>> {
+        let current_config = self.read_named_register(Register::Config)?;
+        let new_config = if active_high {
+            set_bit(current_config, 11)
+        } else {
+            clear_bit(current_config, 11)
+        };
+        self.write_named_register(Register::Config, new_config)?;
+        if self.read_named_register(Register::Config)? != new_config {
+            return Err(Error::WriteVerifyFailed(Register::Config as u8));
+        }
         Ok(())
     }
 
+    /// Read whether the ALRT pin is active high (Config.ALRTp)
+    pub fn read_alert_polarity(&mut self) -> Result<bool, Error<E>> {
+        let config = self.read_named_register(Register::Config)?;
+        Ok(config & (1 << 11) != 0)
+    }
+
     /// Enable alert shutdown. When ALSH = 1, if the ALRT pin = 1, the device will
     /// enter shutdown mode. Default = disabled.
+    ///
+    /// ALSH lives in nConfig, a nonvolatile register. This writes the shadow RAM
+    /// copy only, and does nothing if the bit is already in the requested state;
+    /// the setting is lost on reset unless commit_nonvolatile_memory is called,
+    /// which uses one of the 7 lifetime NVM writes. Write protection is lifted
+    /// for the write and restored afterwards, and a write the chip ignored
+    /// returns Error::WriteProtected.
     pub fn set_alert_shutdown_enable(&mut self, enable: bool) -> Result<(), Error<E>> {
         let current_nconfig = self.read_named_register_nvm(RegisterNvm::NConfig)?;
         let new_nconfig = if enable {
@@ -341,7 +2104,75 @@ where
         } else {
             clear_bit(current_nconfig, 5)
         };
-        self.write_named_register_nvm(RegisterNvm::NConfig, new_nconfig)
+        if new_nconfig == current_nconfig {
+            return Ok(());
+        }
+        self.configure(|bat| bat.write_nvm_checked(RegisterNvm::NConfig, new_nconfig))
+    }
+
+    /// Read everything that decides when the ALRT pin fires: the enables and
+    /// sticky bits in Config, Config2 and nConfig, and all four threshold pairs.
+    /// Protection faults are always latched in ProtAlrt regardless of these.
+    pub fn read_alert_configuration(&mut self) -> Result<AlertConfig, Error<E>> {
+        let config = self.read_named_register(Register::Config)?;
+        let config2 = self.read_named_register(Register::Config2)?;
+        let nconfig = self.read_named_register_nvm(RegisterNvm::NConfig)?;
+        let (min_i, max_i) = self.read_current_alert_threshold()?;
+        Ok(AlertConfig {
+            output_enabled: config & (1 << 2) != 0,
+            active_high: config & (1 << 11) != 0,
+            shutdown_on_alert: nconfig & (1 << 5) != 0,
+            voltage_sticky: config & (1 << 12) != 0,
+            temperature_sticky: config & (1 << 13) != 0,
+            soc_sticky: config & (1 << 14) != 0,
+            temperature_alert_enabled: config2 & (1 << 6) != 0,
+            soc_change_alert_enabled: config2 & (1 << 7) != 0,
+            voltage_threshold_v: self.read_volatage_alert_threshold()?,
+            temperature_threshold_c: self.read_temperature_alert_threshold()?,
+            soc_threshold_percent: self.read_state_of_charge_alert_threshold()?,
+            current_threshold_ma: (
+                min_i as f32 * IALRTTH_LSB_UV / self.r_sense,
+                max_i as f32 * IALRTTH_LSB_UV / self.r_sense,
+            ),
+        })
+    }
+
+    /// Read which enabled alert conditions route to the physical ALRT pin, as
+    /// opposed to only setting Status bits. Protection faults are reported through
+    /// ProtAlrt and are not included.
+    pub fn read_alrt_pin_sources(&mut self) -> Result<AlrtPinSources, Error<E>> {
+        let config = self.read_named_register(Register::Config)?;
+        let config2 = self.read_named_register(Register::Config2)?;
+        let thresholds = [
+            self.read_named_register(Register::VAlrtTh)?,
+            self.read_named_register(Register::TAlrtTh)?,
+            self.read_named_register(Register::SAlrtTh)?,
+            self.read_named_register(Register::IAlrtTh)?,
+        ];
+        Ok(alrt_pin_sources(config, config2, thresholds))
+    }
+
+    /// Set all four alert threshold pairs at once. Everything is validated before
+    /// anything is written, so a bad field leaves the existing thresholds intact
+    /// and returns Error::InvalidAlertThreshold naming it.
+    pub fn set_all_alert_thresholds(
+        &mut self,
+        thresholds: AlertThresholds,
+    ) -> Result<(), Error<E>> {
+        let (min_v, max_v) = thresholds.voltage_v;
+        if !is_valid_voltage_threshold(min_v) || !is_valid_voltage_threshold(max_v) {
+            return Err(Error::InvalidAlertThreshold(AlertThresholdField::Voltage));
+        }
+        thresholds
+            .validate()
+            .map_err(Error::InvalidAlertThreshold)?;
+        self.set_voltage_alert_threshold(min_v, max_v)?;
+        let (min_t, max_t) = thresholds.temperature_c;
+        self.set_temperature_alert_threshold(min_t, max_t)?;
+        let (min_soc, max_soc) = thresholds.soc_percent;
+        self.set_state_of_charge_alert_threshold(min_soc, max_soc)?;
+        let (min_i, max_i) = thresholds.current;
+        self.set_current_alert_threshold(min_i, max_i)
     }
 
     /// Set the upper and lower limits that generate an ALRT pin interrupt if exceeded
@@ -356,10 +2187,7 @@ where
         if !is_valid_voltage_threshold(min_v) {
             return Err(Error::InvalidConfigurationValue(min_v as u16));
         }
-        let threshold_array = [
-            (max_v / VALRTTH_LSB_RESOLUTION) as u8,
-            (min_v / VALRTTH_LSB_RESOLUTION) as u8,
-        ];
+        let threshold_array = [voltage_threshold_code(max_v), voltage_threshold_code(min_v)];
         let threshold_code = u16::from_be_bytes(threshold_array);
         self.write_named_register(Register::VAlrtTh, threshold_code)?;
         Ok(())
@@ -370,8 +2198,8 @@ where
         let code = self.read_named_register(Register::VAlrtTh)?;
         let raw = code.to_be_bytes();
         Ok((
-            raw[0] as f32 * VALRTTH_LSB_RESOLUTION, // Min
-            raw[1] as f32 * VALRTTH_LSB_RESOLUTION, // Max
+            raw[1] as f32 * VALRTTH_LSB_RESOLUTION, // Min
+            raw[0] as f32 * VALRTTH_LSB_RESOLUTION, // Max
         ))
     }
 
@@ -396,8 +2224,8 @@ where
         let code = self.read_named_register(Register::TAlrtTh)?;
         let raw = code.to_be_bytes();
         Ok((
-            raw[0] as i8, // Min
-            raw[1] as i8, // Max
+            raw[1] as i8, // Min
+            raw[0] as i8, // Max
         ))
     }
 
@@ -423,8 +2251,8 @@ where
         let code = self.read_named_register(Register::SAlrtTh)?;
         let raw = code.to_be_bytes();
         Ok((
-            raw[0] as u8, // Min
-            raw[1] as u8, // Max
+            raw[1], // Min
+            raw[0], // Max
         ))
     }
 
@@ -445,51 +2273,530 @@ where
         let code = self.read_named_register(Register::IAlrtTh)?;
         let raw = code.to_be_bytes();
         Ok((
-            raw[0] as i8, // Min
-            raw[1] as i8, // Max
+            raw[1] as i8, // Min
+            raw[0] as i8, // Max
         ))
     }
 }
 
 const VALRTTH_LSB_RESOLUTION: f32 = 0.02; // mV
 
+/// Alert threshold codes that can never trip, (max << 8 | min), in the order
+/// VAlrtTh, TAlrtTh, SAlrtTh, IAlrtTh
+const ALERT_THRESHOLD_DISABLED: [u16; 4] = [0xFF00, 0x7F80, 0xFF00, 0x7F80];
+
+/// Decode Config, Config2 and the VAlrtTh, TAlrtTh, SAlrtTh, IAlrtTh codes into
+/// the alert sources that drive the ALRT pin
+fn alrt_pin_sources(config: u16, config2: u16, thresholds: [u16; 4]) -> AlrtPinSources {
+    let pin_enabled = config & (1 << 2) != 0;
+    let armed = |i: usize| {
+        let [max, min] = thresholds[i].to_be_bytes();
+        let [max_off, min_off] = ALERT_THRESHOLD_DISABLED[i].to_be_bytes();
+        pin_enabled && (max != max_off || min != min_off)
+    };
+    AlrtPinSources {
+        pin_enabled,
+        voltage: armed(0),
+        temperature: armed(1) && config2 & (1 << 6) != 0,
+        soc: armed(2),
+        current: armed(3),
+        soc_change: pin_enabled && config2 & (1 << 7) != 0,
+    }
+}
+
+/// IAlrtTh LSB across the sense resistor; dividing by r_sense (mΩ) gives mA
+const IALRTTH_LSB_UV: f32 = 400.0;
+
 fn is_valid_voltage_threshold(raw: f32) -> bool {
-    raw % VALRTTH_LSB_RESOLUTION < 0.0001 && raw >= 0.0 && raw <= (255.0 * VALRTTH_LSB_RESOLUTION)
+    (0.0..=(255.0 * VALRTTH_LSB_RESOLUTION)).contains(&raw)
+        && (voltage_threshold_code(raw) as f32 * VALRTTH_LSB_RESOLUTION - raw).abs() < 0.0001
 }
 
-fn convert_to_time(raw: u16) -> f32 {
-    raw as f32 * 5.625
+/// Nearest VAlrtTh code; plain truncation turns e.g. 4.2V into 4.18V
+fn voltage_threshold_code(raw: f32) -> u8 {
+    (raw / VALRTTH_LSB_RESOLUTION + 0.5) as u8
 }
 
-fn convert_to_voltage(raw: u16) -> f32 {
-    raw as f32 * 0.078125 / 1000.0
+/// Linear interpolation of SOC from a non-empty (voltage, SOC) table sorted by voltage
+fn interpolate_ocv_table(table: &[(f32, f32)], voltage: f32) -> f32 {
+    let (first_v, first_soc) = table[0];
+    if voltage <= first_v {
+        return first_soc;
+    }
+    for pair in table.windows(2) {
+        let (v0, soc0) = pair[0];
+        let (v1, soc1) = pair[1];
+        if voltage <= v1 {
+            return soc0 + (soc1 - soc0) * (voltage - v0) / (v1 - v0);
+        }
+    }
+    table[table.len() - 1].1
 }
 
-fn convert_to_percentage(raw: u16) -> f32 {
-    raw as f32 / 256.0
+const UVP_BASE_V: f32 = 2.0;
+const UVP_LSB_V: f32 = 0.01;
+const UVP_HYSTERESIS_LSB_V: f32 = 0.02;
+
+/// Smallest undervoltage recovery hysteresis that avoids protection chatter
+pub const MIN_UNDERVOLTAGE_HYSTERESIS_V: f32 = 0.04;
+
+/// nUVPrtTh bits 15:8 = (trip - 2.0V) / 10mV, bits 7:4 = hysteresis / 20mV.
+/// The error is the out of range voltage in mV.
+fn encode_undervoltage_threshold(trip_v: f32, hysteresis_v: f32) -> Result<u16, u16> {
+    let trip = (trip_v - UVP_BASE_V) / UVP_LSB_V + 0.5;
+    if !(0.0..256.0).contains(&trip) {
+        return Err((trip_v * 1000.0) as u16);
+    }
+    let hysteresis = hysteresis_v / UVP_HYSTERESIS_LSB_V + 0.5;
+    if !(0.0..16.0).contains(&hysteresis) {
+        return Err((hysteresis_v * 1000.0) as u16);
+    }
+    Ok((trip as u16) << 8 | (hysteresis as u16) << 4)
 }
 
-fn convert_to_temperature(raw: i16) -> f32 {
-    raw as f32 / 256.0
+fn decode_undervoltage_threshold(code: u16) -> (f32, f32) {
+    (
+        UVP_BASE_V + (code >> 8) as f32 * UVP_LSB_V,
+        ((code >> 4) & 0x0F) as f32 * UVP_HYSTERESIS_LSB_V,
+    )
 }
 
-fn convert_to_capacity(raw: u16, r_sense: f32) -> f32 {
-    raw as f32 * 5.0 / r_sense
+const VEMPTY_LSB_V: f32 = 0.01;
+const VEMPTY_RECOVERY_LSB_V: f32 = 0.04;
+
+const HIB_ENTER_TIME_LSB_US: u64 = 2_812_000;
+
+/// Largest HibThreshold current not above entry_current_ma, as the exponent n in
+/// full_cap_mah / 0.8h / 2^n
+fn encode_hib_threshold(full_cap_mah: f32, entry_current_ma: f32) -> Option<u8> {
+    (0..16u8).find(|n| full_cap_mah / 0.8 / (1u32 << n) as f32 <= entry_current_ma)
 }
 
-fn convert_to_current(raw: i16, r_sense: f32) -> f32 {
-    raw as f32 * 1.5625 / (r_sense / 1000.0)
+/// Smallest HibEnterTime exponent n with 2.812s x 2^n >= entry_time
+fn encode_hib_enter_time(entry_time: Duration) -> Option<u8> {
+    let us = entry_time.as_micros() as u64;
+    (0..8u8).find(|n| HIB_ENTER_TIME_LSB_US << n >= us)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::println;
-    #[test]
-    fn max_temp_conversion() {
-        let max_temp_raw: u16 = 0b01111111_11111111;
-        let temp = convert_to_temperature(max_temp_raw as i16);
-        println!("temp {}", temp);
+/// mA / mAh gives C directly, so no unit conversion is needed here
+fn c_rate(current_ma: f32, capacity_mah: f32) -> f32 {
+    if capacity_mah <= 0.0 {
+        return 0.0;
+    }
+    current_ma / capacity_mah
+}
+
+/// nPackCfg.NThrms, the number of enabled thermistor channels
+const PACK_CFG_N_THERMS_MASK: u16 = 0b111 << 2;
+
+/// Pack the set_pack_config arguments into nPackCfg
+fn encode_pack_config<E>(
+    n_cells: u8,
+    n_therms: u8,
+    therm_type: ThermistorType,
+    charge_pump_voltage_config: ChargePumpVoltageConfiguration,
+    always_on_regulator_config: AlwaysOnRegulatorConfiguration,
+    battery_pack_update: BatteryPackUpdate,
+) -> Result<u16, Error<E>> {
+    if !(2..=4).contains(&n_cells) {
+        return Err(Error::UnsupportedCellCount(n_cells));
+    }
+    let n_cells = n_cells - 2;
+    if n_therms > 4 {
+        return Err(Error::InvalidConfigurationValue(n_therms as u16));
+    }
+    let n_therms = n_therms << 2;
+
+    Ok(n_cells as u16
+        | n_therms as u16
+        | therm_type as u16
+        | charge_pump_voltage_config as u16
+        | always_on_regulator_config as u16
+        | battery_pack_update as u16)
+}
+
+fn capacity_temperature_factor(temp_c: f32) -> f32 {
+    (1.0 - CAPACITY_TEMPCO_PER_C * (CAPACITY_REFERENCE_C - temp_c).max(0.0)).max(0.0)
+}
+
+fn project_capacity(capacity: f32, from_c: f32, to_c: f32) -> f32 {
+    let from = capacity_temperature_factor(from_c);
+    if from == 0.0 {
+        return 0.0;
+    }
+    capacity * capacity_temperature_factor(to_c) / from
+}
+
+fn rescale_soc(soc: f32, (empty, full): (f32, f32)) -> f32 {
+    ((soc - empty) / (full - empty) * 100.0).clamp(0.0, 100.0)
+}
+
+fn capacity_fade_percent(full: f32, design: f32) -> f32 {
+    ((1.0 - full / design) * 100.0).clamp(0.0, 100.0)
+}
+
+/// ProtStatus faults in bit order; Ship and Full are states rather than faults
+const PROT_STATUS_FAULTS: [ProtStatusCode; 14] = [
+    ProtStatusCode::ResDFault,
+    ProtStatusCode::OverdischargeCurrent,
+    ProtStatusCode::Undervoltage,
+    ProtStatusCode::OvertemperatureDischarging,
+    ProtStatusCode::OvertemperatureDie,
+    ProtStatusCode::PermFail,
+    ProtStatusCode::MulticellImbalance,
+    ProtStatusCode::PrequalTimeout,
+    ProtStatusCode::CapacityOverflow,
+    ProtStatusCode::OverchargeCurrent,
+    ProtStatusCode::Overvoltage,
+    ProtStatusCode::UndertemperatureCharging,
+    ProtStatusCode::OvertemperatureCharging,
+    ProtStatusCode::ChargeWatchDogTimer,
+];
+
+fn discharge_fault_reason(prot_status: u16) -> DischargeDisabledReason {
+    let overtemperature = ProtStatusCode::OvertemperatureDischarging as u16
+        | ProtStatusCode::OvertemperatureDie as u16;
+    if has_code(ProtStatusCode::Undervoltage as u16, prot_status) {
+        DischargeDisabledReason::Undervoltage
+    } else if has_code(ProtStatusCode::OverdischargeCurrent as u16, prot_status) {
+        DischargeDisabledReason::Overcurrent
+    } else if has_code(overtemperature, prot_status) {
+        DischargeDisabledReason::Overtemperature
+    } else {
+        DischargeDisabledReason::OtherProtection
+    }
+}
+
+/// PCKP above BATT by more than this means an external source is present (V)
+const PACK_CHARGER_MARGIN_V: f32 = 0.1;
+
+/// Current magnitude below this is treated as no load or charger (mA)
+const PACK_IDLE_CURRENT_MA: f32 = 5.0;
+
+fn classify_pack_connection(batt_v: f32, pckp_v: f32, current_ma: f32) -> PackConnection {
+    if current_ma > PACK_IDLE_CURRENT_MA || pckp_v > batt_v + PACK_CHARGER_MARGIN_V {
+        PackConnection::ChargerPresent
+    } else if current_ma < -PACK_IDLE_CURRENT_MA {
+        PackConnection::Connected
+    } else {
+        PackConnection::Disconnected
+    }
+}
+
+fn fet_voltage_drop(batt_v: f32, pckp_v: f32, current_ma: f32) -> FetVoltageDrop {
+    let drop_v = batt_v - pckp_v;
+    // V / mA is kΩ, so scale by 1e6 for mΩ. Discharge gives a positive drop
+    // with negative current, so the magnitudes are used.
+    let resistance_mohm = (current_ma.abs() > PACK_IDLE_CURRENT_MA)
+        .then(|| drop_v.abs() / current_ma.abs() * 1_000_000.0);
+    FetVoltageDrop {
+        drop_v,
+        current_ma,
+        resistance_mohm,
+    }
+}
+
+fn body_diode_conduction(
+    charge_on: bool,
+    discharge_on: bool,
+    current_ma: f32,
+) -> BodyDiodeConduction {
+    if !discharge_on && current_ma > PACK_IDLE_CURRENT_MA {
+        BodyDiodeConduction::DischargeFet
+    } else if !charge_on && current_ma < -PACK_IDLE_CURRENT_MA {
+        BodyDiodeConduction::ChargeFet
+    } else {
+        BodyDiodeConduction::None
+    }
+}
+
+fn classify_pack_health(
+    perm_fail_latch: u16,
+    prot_status: u16,
+    current: &CurrentSenseHealth,
+    gauge: &GaugeHealth,
+) -> PackHealth {
+    if perm_fail_latch != 0 || has_code(ProtStatusCode::PermFail as u16, prot_status) {
+        return PackHealth::PermanentFail(perm_fail_latch);
+    }
+    if let Some(fault) = PROT_STATUS_FAULTS
+        .iter()
+        .find(|code| has_code(**code as u16, prot_status))
+    {
+        return PackHealth::Fault(*fault);
+    }
+    if !current.is_trusted() {
+        return PackHealth::Degraded(DegradedReason::CurrentSenseUntrusted);
+    }
+    if gauge.needs_recalibration {
+        return PackHealth::Degraded(DegradedReason::GaugeNeedsRecalibration);
+    }
+    PackHealth::Ok
+}
+
+/// Each used write cycle sets one bit in both bytes of nRemainingUpdates
+fn remaining_nvm_writes(code: u16) -> u8 {
+    let [high, low] = code.to_be_bytes();
+    NVM_WRITE_CYCLES.saturating_sub((high | low).count_ones() as u8)
+}
+
+/// NVM write cycles over the life of the part
+const NVM_WRITE_CYCLES: u8 = 7;
+
+fn cells_agree_with_pack(cells: &[f32], pack_v: f32, tolerance_v: f32) -> bool {
+    let sum: f32 = cells.iter().sum();
+    (sum - pack_v).abs() <= tolerance_v
+}
+
+/// Scaling of each register with a physical unit, matching the dedicated read methods
+fn physical_value(reg: Register, raw: u16, r_sense: f32) -> PhysicalValue {
+    use Register::*;
+    match reg {
+        VCell | AvgVCell | Cell1 | Cell2 | Cell3 | Cell4 | AvgCell1 | AvgCell2 | AvgCell3
+        | AvgCell4 | VFOcv => PhysicalValue::Voltage(convert_to_voltage(raw)),
+        Batt | Pckp => PhysicalValue::Voltage(convert_to_pack_voltage(raw)),
+        Current | AvgCurrent | AtRate => {
+            PhysicalValue::Current(convert_to_current(raw as i16, r_sense))
+        }
+        Temp | DieTemp => PhysicalValue::Temperature(convert_to_temperature(raw as i16)),
+        RepSoc | MixSoc | VFSoc | Age => PhysicalValue::Percentage(convert_to_percentage(raw)),
+        TimeToEmpty | TimeToFull | AtTte => PhysicalValue::Time(convert_to_time(raw)),
+        RepCap | FullCapRep | FullCapNom | MixCap | AvCap => {
+            PhysicalValue::Capacity(convert_to_capacity(raw, r_sense))
+        }
+        _ => PhysicalValue::Raw(raw),
+    }
+}
+
+/// Timer LSB is 175.8ms (exactly 45s / 256); TimerH counts Timer rollovers
+fn convert_to_operating_time(timer_h: u16, timer: u16) -> Duration {
+    let ticks = (timer_h as u64) << 16 | timer as u64;
+    Duration::from_micros(ticks * 45_000_000 / 256)
+}
+
+fn convert_to_time(raw: u16) -> f32 {
+    raw as f32 * 5.625
+}
+
+fn convert_to_voltage(raw: u16) -> f32 {
+    raw as f32 * 0.078125 / 1000.0
+}
+
+/// Batt and Pckp have a 0.3125mV LSB (20.48V full scale), 4x that of the
+/// per-cell registers. See the Batt (0DAh) and PCKP (0DBh) register
+/// descriptions in the MAX17320 datasheet.
+fn convert_to_pack_voltage(raw: u16) -> f32 {
+    raw as f32 * 0.3125 / 1000.0
+}
+
+fn convert_to_percentage(raw: u16) -> f32 {
+    raw as f32 / 256.0
+}
+
+fn convert_to_temperature(raw: i16) -> f32 {
+    raw as f32 / 256.0
+}
+
+fn convert_to_temperature_fahrenheit(raw: i16) -> f32 {
+    raw as f32 * 9.0 / (5.0 * 256.0) + 32.0
+}
+
+/// Capacity registers have a 5.0µVh LSB across the sense resistor, so with
+/// r_sense in mΩ the LSB is 5.0 / r_sense mAh (0.5mAh at the datasheet's 10mΩ,
+/// for a 0 to 32.7675Ah range). The µVh / mΩ = mAh relation holds for any
+/// r_sense, so no other correction is needed.
+fn convert_to_capacity(raw: u16, r_sense: f32) -> f32 {
+    raw as f32 * CAPACITY_LSB_UVH / r_sense
+}
+
+/// Same scaling as convert_to_capacity for two's complement charge registers
+fn convert_to_charge(raw: i16, r_sense: f32) -> f32 {
+    raw as f32 * CAPACITY_LSB_UVH / r_sense
+}
+
+const CAPACITY_LSB_UVH: f32 = 5.0;
+
+/// Current LSB is 1.5625µV / r_sense, which with r_sense in mΩ gives mA
+fn convert_to_current(raw: i16, r_sense: f32) -> f32 {
+    raw as f32 * CURRENT_LSB_UV / r_sense
+}
+
+const CURRENT_LSB_UV: f32 = 1.5625;
+
+/// Current is updated once per gauge task period
+const CURRENT_UPDATE_PERIOD_US: u32 = 351_500;
+
+/// nProtCfg.ChgWDTEn, enables the charge communication watchdog
+const CHARGE_WATCHDOG_ENABLE: u16 = 1 << 11;
+
+/// nHibCfg.HibScalar, bits 2:0
+const HIB_SCALAR_MASK: u16 = 0x0007;
+
+/// Hibernate task period for a HibScalar value
+fn hibernate_period(scalar: u16) -> Duration {
+    Duration::from_micros(CURRENT_UPDATE_PERIOD_US as u64) * (1 << scalar)
+}
+
+/// HibScalar whose period is nearest to period on a log scale
+fn hib_scalar_for(period: Duration) -> Option<u16> {
+    if period.is_zero() || period > hibernate_period(HIB_SCALAR_MASK) * 2 {
+        return None;
+    }
+    (0..=HIB_SCALAR_MASK).min_by(|a, b| {
+        let ratio = |n: u16| {
+            let r = period.as_secs_f32() / hibernate_period(n).as_secs_f32();
+            if r < 1.0 {
+                1.0 / r
+            } else {
+                r
+            }
+        };
+        ratio(*a).total_cmp(&ratio(*b))
+    })
+}
+
+/// Status bits reported by poll_alerts: every alert, but not POR
+const ALERT_STATUS_BITS: u16 = StatusCode::MinCurrentExceeded as u16
+    | StatusCode::MaxCurrentExceeded as u16
+    | StatusCode::Soc1PercentChange as u16
+    | StatusCode::MinVoltageExceeded as u16
+    | StatusCode::MinTemperatureExceeded as u16
+    | StatusCode::MinSocExceeded as u16
+    | StatusCode::MaxVoltageExceeded as u16
+    | StatusCode::MaxTemperatureExceeded as u16
+    | StatusCode::MaxSocExceeded as u16
+    | StatusCode::ProtectionAlert as u16;
+
+/// Cycles counts 16% of a full cycle per LSB, the same unit as the NVM
+/// auto-save interval (AutoSaveConfig)
+const CYCLES_LSB: f32 = 0.16;
+
+/// CGain is a two's complement gain where 0x0400 is unity
+const CGAIN_UNITY: f32 = 1024.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::println;
+
+    /// Register file for the main (0x36) and NVM (0x0B) addresses
+    struct MockI2c {
+        main: [u16; 256],
+        nvm: [u16; 256],
+        fail: bool,
+        comm_stat_writes: Vec<u16>,
+        nvm_read_only: Option<usize>,
+        fail_nvm_writes: bool,
+        model_refreshes: usize,
+    }
+
+    impl MockI2c {
+        fn new() -> Self {
+            Self {
+                main: [0; 256],
+                nvm: [0; 256],
+                fail: false,
+                comm_stat_writes: Vec::new(),
+                nvm_read_only: None,
+                fail_nvm_writes: false,
+                model_refreshes: 0,
+            }
+        }
+
+        fn regs(&mut self, address: u8) -> &mut [u16; 256] {
+            if address == 0x0B {
+                &mut self.nvm
+            } else {
+                &mut self.main
+            }
+        }
+    }
+
+    impl Write for MockI2c {
+        type Error = ();
+        fn write(&mut self, _address: u8, _bytes: &[u8]) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    impl Read for MockI2c {
+        type Error = ();
+        fn read(&mut self, _address: u8, _buffer: &mut [u8]) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    impl WriteRead for MockI2c {
+        type Error = ();
+        fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), ()> {
+            if self.fail {
+                return Err(());
+            }
+            let reg = bytes[0] as usize;
+            if bytes.len() == 3 && address == 0x0B && self.fail_nvm_writes {
+                return Err(());
+            }
+            if bytes.len() == 3 && address == 0x0B && self.nvm_read_only == Some(reg) {
+                return Ok(());
+            }
+            let regs = self.regs(address);
+            if bytes.len() == 3 {
+                let code = u16::from_le_bytes([bytes[1], bytes[2]]);
+                regs[reg] = code;
+                if address != 0x0B && reg == Register::CommStat as usize {
+                    self.comm_stat_writes.push(code);
+                }
+                // The model reload completes instantly
+                let refresh = ModelCfgCode::Refresh as u16;
+                if address != 0x0B && reg == Register::ModelCfg as usize && code & refresh != 0 {
+                    self.model_refreshes += 1;
+                    self.main[reg] = code & !refresh;
+                }
+            } else {
+                for (i, word) in buffer.chunks_exact_mut(2).enumerate() {
+                    word.copy_from_slice(&regs[reg + i].to_le_bytes());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn register_writes_are_lsb_first() {
+        assert_eq!(
+            i2c_interface::write_frame(Register::Command as u8, CommandCode::CopyNvBlock as u16),
+            [0x60, 0x04, 0xE9]
+        );
+        assert_eq!(
+            i2c_interface::write_frame(Register::CommStat as u8, 0x00F9),
+            [0x61, 0xF9, 0x00]
+        );
+    }
+
+    #[test]
+    fn block_read_leaves_output_untouched_on_failure() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm[0x90] = 0x1234;
+        i2c.nvm[0x91] = 0x5678;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let mut out = [0; 2];
+        bat.read_block(0x90, 0x0B, &mut out).unwrap();
+        assert_eq!(out, [0x1234, 0x5678]);
+
+        bat.com.fail = true;
+        let mut out = [0xAAAA; 2];
+        assert_eq!(
+            bat.read_block(0x90, 0x0B, &mut out),
+            Err(Error::NvmAddressUnreachable(()))
+        );
+        assert_eq!(out, [0xAAAA; 2]);
+    }
+
+    #[test]
+    fn max_temp_conversion() {
+        let max_temp_raw: u16 = 0b01111111_11111111;
+        let temp = convert_to_temperature(max_temp_raw as i16);
+        println!("temp {}", temp);
         assert_eq!(temp, 127.99609)
     }
     #[test]
@@ -500,8 +2807,860 @@ mod tests {
         assert_eq!(temp, -128.0)
     }
 
+    #[test]
+    fn fahrenheit_conversion() {
+        assert_eq!(convert_to_temperature_fahrenheit(0), 32.0);
+        assert_eq!(convert_to_temperature_fahrenheit(100 * 256), 212.0);
+        assert_eq!(convert_to_temperature_fahrenheit(-40 * 256), -40.0);
+    }
+
+    #[test]
+    fn protection_history_decode() {
+        let history = ProtectionHistory::from_registers(0x3CF6, 0xD2A0, 0x19E7, 0x0008, 4.0);
+        assert_eq!(history.max_temperature, 60);
+        assert_eq!(history.min_temperature, -10);
+        assert!((history.max_voltage - 4.2).abs() < 1e-4);
+        assert!((history.min_voltage - 3.2).abs() < 1e-4);
+        assert_eq!(history.max_current, 2500.0);
+        assert_eq!(history.min_current, -2500.0);
+        assert_eq!(history.fault_flags, 0x0008);
+    }
+
+    #[test]
+    fn write_protection_pages_bits() {
+        assert_eq!(WriteProtectionPages::ALL.to_bits(), 0x00F9);
+        assert_eq!(WriteProtectionPages::NONE.to_bits(), 0x0000);
+        let pages = WriteProtectionPages {
+            wp3: false,
+            ..WriteProtectionPages::ALL
+        };
+        assert_eq!(pages.to_bits(), 0x00D8);
+        assert_eq!(WriteProtectionPages::from_bits(pages.to_bits()), pages);
+    }
+
+    #[test]
+    fn write_protection_survives_setters() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        let pages = WriteProtectionPages {
+            wp3: false,
+            ..WriteProtectionPages::ALL
+        };
+        bat.set_write_protection(pages).unwrap();
+        bat.set_fet_configuration(FetConfiguration {
+            charge_pump: ChargePumpVoltageConfiguration::Cp8V,
+        })
+        .unwrap();
+        assert_eq!(bat.read_write_protection(), Ok(pages));
+
+        bat.configure(|bat| bat.set_write_protection(WriteProtectionPages::NONE))
+            .unwrap();
+        assert_eq!(bat.read_write_protection(), Ok(WriteProtectionPages::NONE));
+        bat.configure(|_| Ok(())).unwrap();
+        assert_eq!(bat.read_write_protection(), Ok(WriteProtectionPages::NONE));
+    }
+
+    #[test]
+    fn alert_output_enable_clears_shutdown_under_unlock() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.nvm[RegisterNvm::NConfig as usize] = 1 << 5;
+        bat.set_alert_output_enable(true).unwrap();
+        assert_eq!(bat.com.main[Register::Config as usize], 1 << 2);
+        assert_eq!(bat.com.nvm[RegisterNvm::NConfig as usize], 0);
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+
+        bat.set_alert_polarity(true).unwrap();
+        assert_eq!(bat.read_alert_polarity(), Ok(true));
+    }
+
+    #[test]
+    fn telemetry_change_uses_deadbands() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.main[Register::Timer as usize] = 100;
+        let first = bat.read_sample().unwrap();
+        let (changed, second) = bat.has_telemetry_changed(&first).unwrap();
+        assert!(!changed);
+        assert_eq!(second, first);
+
+        let noisy = Sample {
+            current_ma: first.current_ma + 0.5,
+            ..first
+        };
+        assert!(!noisy.has_changed_from(&first));
+        let ticked = Sample {
+            timer: first.timer + 1,
+            ..first
+        };
+        assert!(ticked.has_changed_from(&first));
+        let charged = Sample {
+            state_of_charge: first.state_of_charge + 1.0,
+            ..first
+        };
+        assert!(charged.has_changed_from(&first));
+    }
+
+    #[test]
+    fn standard_preset_refreshes_model_once() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.configure_standard_liion_3s(3000.0).unwrap();
+        assert_eq!(bat.com.model_refreshes, 1);
+        assert_eq!(bat.read_design_capacity(), Ok(3000.0));
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+    }
+
+    #[test]
+    fn ocv_table_interpolation() {
+        let table = [(3.0, 0.0), (3.6, 20.0), (4.2, 100.0)];
+        assert_eq!(interpolate_ocv_table(&table, 2.5), 0.0);
+        assert_eq!(interpolate_ocv_table(&table, 3.3), 10.0);
+        assert_eq!(interpolate_ocv_table(&table, 3.6), 20.0);
+        assert_eq!(interpolate_ocv_table(&table, 4.5), 100.0);
+    }
+
+    #[test]
+    fn hibernate_encoding() {
+        // 3200mAh / 0.8h = 4000mA, halved per step
+        assert_eq!(encode_hib_threshold(3200.0, 4000.0), Some(0));
+        assert_eq!(encode_hib_threshold(3200.0, 200.0), Some(5));
+        assert_eq!(encode_hib_threshold(3200.0, 0.01), None);
+        assert_eq!(encode_hib_enter_time(Duration::from_secs(0)), Some(0));
+        assert_eq!(encode_hib_enter_time(Duration::from_secs(45)), Some(5));
+        assert_eq!(encode_hib_enter_time(Duration::from_secs(400)), None);
+    }
+
+    #[test]
+    fn manufacture_date_packing() {
+        let date = ManufactureDate {
+            year: 2023,
+            month: 6,
+            day: 15,
+        };
+        assert_eq!(date.to_bits(), Some(43 << 9 | 6 << 5 | 15));
+        assert_eq!(ManufactureDate::from_bits(date.to_bits().unwrap()), date);
+        assert_eq!(ManufactureDate { month: 13, ..date }.to_bits(), None);
+    }
+
+    #[test]
+    fn undervoltage_threshold_encoding() {
+        let code = encode_undervoltage_threshold(2.8, 0.1).unwrap();
+        assert_eq!(code, 80 << 8 | 5 << 4);
+        let (trip, hysteresis) = decode_undervoltage_threshold(code);
+        assert!((trip - 2.8).abs() < 1e-4);
+        assert!((hysteresis - 0.1).abs() < 1e-4);
+        assert_eq!(encode_undervoltage_threshold(1.9, 0.1), Err(1900));
+        assert_eq!(encode_undervoltage_threshold(2.8, 0.4), Err(400));
+    }
+
+    #[test]
+    fn c_rate_calculation() {
+        assert_eq!(c_rate(-1500.0, 3000.0), -0.5);
+        assert_eq!(c_rate(6000.0, 3000.0), 2.0);
+        assert_eq!(c_rate(100.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn restore_verifies_and_counts_writes() {
+        // The mock echoes writes back, so a clean restore verifies and commits
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        let image = [0x1234; NVM_SHADOW_LEN];
+        assert_eq!(bat.restore_nonvolatile_memory(&image), Ok(()));
+        // The read-only tail, including the update counter, is left alone
+        assert_eq!(bat.com.nvm[0xDF], 0x1234);
+        assert_eq!(bat.com.nvm[0xED], 0);
+        assert_eq!(remaining_nvm_writes(0x0000), 7);
+        assert_eq!(remaining_nvm_writes(0x0303), 5);
+        assert_eq!(remaining_nvm_writes(0x7F7F), 0);
+        assert_eq!(remaining_nvm_writes(0xFFFF), 0);
+    }
+
+    #[test]
+    fn block_copy_error_is_reported_without_a_register() {
+        use sequence::{Action, NvmSequence};
+        let nv_error = CommStatCode::NonvolatileError as u16;
+        let mut copy = NvmSequence::command(CommandCode::CopyNvBlock, None);
+        for _ in 0..3 {
+            copy.step::<()>(0).unwrap();
+        }
+        assert_eq!(
+            copy.step::<()>(nv_error),
+            Ok(Action::Read(Register::CommStat))
+        );
+        assert_eq!(copy.step::<()>(nv_error), Err(Error::NvmCopyFailed));
+
+        let mut wait = NvmSequence::wait(RegisterNvm::NDesignCap);
+        wait.step::<()>(0).unwrap();
+        wait.step::<()>(nv_error).unwrap();
+        assert_eq!(
+            wait.step::<()>(nv_error),
+            Err(Error::NonvolatileError(RegisterNvm::NDesignCap))
+        );
+    }
+
+    #[test]
+    fn restore_stops_before_writing_when_exhausted() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm[0xED] = 0x7F7F;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let image = [0x1234; NVM_SHADOW_LEN];
+        assert_eq!(
+            bat.restore_nonvolatile_memory(&image),
+            Err(Error::NvmWritesExhausted)
+        );
+        assert_eq!(bat.com.nvm[0x80], 0);
+    }
+
+    #[test]
+    fn restore_relocks_after_bus_error() {
+        let mut i2c = MockI2c::new();
+        i2c.fail_nvm_writes = true;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let image = [0x1234; NVM_SHADOW_LEN];
+        assert_eq!(
+            bat.restore_nonvolatile_memory(&image),
+            Err(Error::NvmAddressUnreachable(()))
+        );
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+    }
+
+    #[test]
+    fn restore_reports_readback_mismatch() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm_read_only = Some(0xB5);
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let image = [0x1234; NVM_SHADOW_LEN];
+        assert_eq!(
+            bat.restore_nonvolatile_memory(&image),
+            Err(Error::NvmVerifyFailed(0x1B5))
+        );
+        assert_ne!(
+            bat.com.main[Register::Command as usize],
+            CommandCode::CopyNvBlock as u16
+        );
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+    }
+
+    #[test]
+    fn capacity_fade_is_clamped() {
+        assert!((capacity_fade_percent(800.0, 1000.0) - 20.0).abs() < 1e-3);
+        assert_eq!(capacity_fade_percent(1050.0, 1000.0), 0.0);
+        assert_eq!(capacity_fade_percent(-1.0, 1000.0), 100.0);
+    }
+
+    #[test]
+    fn time_to_full_config_reports_offending_field() {
+        let config = TimeToFullConfig {
+            cv_start_percent: 80.0,
+            cv_half_time: Duration::from_secs(2000),
+        };
+        assert_eq!(config.encode_checked(), Err(2000));
+        let config = TimeToFullConfig {
+            cv_start_percent: 130.0,
+            cv_half_time: Duration::from_secs(600),
+        };
+        assert_eq!(config.encode_checked(), Err(130));
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        assert_eq!(
+            bat.set_time_to_full_config(config),
+            Err(Error::InvalidConfigurationValue(130))
+        );
+    }
+
+    #[test]
+    fn filter_config_round_trip() {
+        // 0xCEA4 is the power-on default: 5.625s, 45s, 90s
+        let config = FilterConfig::from_bits(0xCEA4);
+        assert_eq!(config.current, Duration::from_micros(5_625_000));
+        assert_eq!(config.voltage, Duration::from_secs(45));
+        assert_eq!(config.temperature, Duration::from_secs(90));
+        assert_eq!(config.to_bits(), Some(0xCEA4 & FilterConfig::MASK));
+        let rounded = FilterConfig {
+            current: Duration::from_secs(20),
+            ..config
+        };
+        assert_eq!(
+            FilterConfig::from_bits(rounded.to_bits().unwrap()).current,
+            Duration::from_micros(22_500_000)
+        );
+        let too_long = FilterConfig {
+            temperature: Duration::from_secs(24 * 3600),
+            ..config
+        };
+        assert_eq!(too_long.to_bits(), None);
+        let bad_voltage = FilterConfig {
+            voltage: Duration::from_secs(3600),
+            ..config
+        };
+        assert_eq!(bad_voltage.encode_checked(), Err(3600));
+    }
+
+    #[test]
+    fn alert_thresholds_read_back_as_min_max() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.set_voltage_alert_threshold(3.0, 4.2).unwrap();
+        bat.set_temperature_alert_threshold(-10, 60).unwrap();
+        // Max in the high byte, min in the low byte (20mV LSB)
+        assert_eq!(bat.com.main[Register::VAlrtTh as usize], 210 << 8 | 150);
+        let config = bat.read_alert_configuration().unwrap();
+        let (min_v, max_v) = config.voltage_threshold_v;
+        assert!((min_v - 3.0).abs() < 1e-3 && (max_v - 4.2).abs() < 1e-3);
+        assert_eq!(config.temperature_threshold_c, (-10, 60));
+    }
+
+    #[test]
+    fn physical_value_scaling() {
+        assert_eq!(
+            physical_value(Register::Temp, 0x1900, 5.0),
+            PhysicalValue::Temperature(25.0)
+        );
+        assert_eq!(
+            physical_value(Register::RepSoc, 0x3200, 5.0),
+            PhysicalValue::Percentage(50.0)
+        );
+        assert_eq!(
+            physical_value(Register::Current, 0xFFF0, 5.0),
+            PhysicalValue::Current(-5.0)
+        );
+        assert_eq!(
+            physical_value(Register::Status, 0x0002, 5.0),
+            PhysicalValue::Raw(0x0002)
+        );
+    }
+
+    #[test]
+    fn pack_health_by_severity() {
+        let current = CurrentSenseHealth {
+            current_ma: 0.0,
+            avg_current_ma: 0.0,
+            saturated: false,
+        };
+        let gauge = GaugeHealth {
+            mix_soc: 50.0,
+            vf_soc: 70.0,
+            divergence: 20.0,
+            needs_recalibration: true,
+        };
+        let undervoltage = ProtStatusCode::Undervoltage as u16;
+        let full = ProtStatusCode::Full as u16;
+        assert_eq!(
+            classify_pack_health(0x0040, undervoltage, &current, &gauge),
+            PackHealth::PermanentFail(0x0040)
+        );
+        assert_eq!(
+            classify_pack_health(0, undervoltage | full, &current, &gauge),
+            PackHealth::Fault(ProtStatusCode::Undervoltage)
+        );
+        assert_eq!(
+            classify_pack_health(0, full, &current, &gauge),
+            PackHealth::Degraded(DegradedReason::GaugeNeedsRecalibration)
+        );
+        let gauge = GaugeHealth {
+            needs_recalibration: false,
+            ..gauge
+        };
+        assert_eq!(classify_pack_health(0, 0, &current, &gauge), PackHealth::Ok);
+    }
+
+    #[test]
+    fn display_soc_reserves_bottom() {
+        assert_eq!(rescale_soc(5.0, (5.0, 100.0)), 0.0);
+        assert_eq!(rescale_soc(2.0, (5.0, 100.0)), 0.0);
+        assert_eq!(rescale_soc(100.0, (5.0, 100.0)), 100.0);
+        assert!((rescale_soc(52.5, (5.0, 100.0)) - 50.0).abs() < 1e-3);
+
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.set_soc_display_range(5.0, 100.0).unwrap();
+        assert_eq!(bat.soc_display_range(), (5.0, 100.0));
+    }
+
+    #[test]
+    fn charge_termination_current_scaling() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.nvm[RegisterNvm::NIChgTerm as usize] = 1600;
+        assert_eq!(bat.read_charge_termination_current(), Ok(500.0));
+    }
+
+    #[test]
+    fn protection_voltage_mode_is_per_cell_only() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        assert_eq!(
+            bat.set_protection_voltage_mode(ProtectionVoltageMode::PerCell),
+            Ok(())
+        );
+        assert_eq!(
+            bat.set_protection_voltage_mode(ProtectionVoltageMode::PackTotal),
+            Err(Error::UnsupportedProtectionVoltageMode)
+        );
+    }
+
+    #[test]
+    fn operating_time_scaling() {
+        assert_eq!(convert_to_operating_time(0, 256), Duration::from_secs(45));
+        // One TimerH LSB is 3.2h
+        assert_eq!(convert_to_operating_time(1, 0), Duration::from_secs(11_520));
+    }
+
+    #[test]
+    fn write_protection_cycle_keeps_fet_overrides() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        let dis_off = CommStatCode::DischargeOff as u16;
+        bat.com.main[Register::CommStat as usize] = dis_off | 0x00F9;
+        bat.unlock_write_protection().unwrap();
+        assert_eq!(bat.com.main[Register::CommStat as usize], dis_off);
+        bat.lock_write_protection().unwrap();
+        assert_eq!(bat.com.main[Register::CommStat as usize], dis_off | 0x00F9);
+    }
+
+    #[test]
+    fn measurement_pairs_are_instantaneous_then_average() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.main[Register::Current as usize] = 32; // 10mA
+        bat.com.main[Register::AvgCurrent as usize] = 16; // 5mA
+        bat.com.main[Register::VCell as usize] = 51200; // 4V
+        bat.com.main[Register::AvgVCell as usize] = 38400; // 3V
+        assert_eq!(bat.read_current_pair(), Ok((10.0, 5.0)));
+        assert_eq!(bat.read_voltage_pair(), Ok((4.0, 3.0)));
+    }
+
+    fn pack_config(n_cells: u8, n_therms: u8) -> Result<u16, Error<()>> {
+        encode_pack_config(
+            n_cells,
+            n_therms,
+            ThermistorType::Ntc10KOhm,
+            ChargePumpVoltageConfiguration::Cp6V,
+            AlwaysOnRegulatorConfiguration::Disabled,
+            BatteryPackUpdate::UpdateEvery22p4s,
+        )
+    }
+
+    #[test]
+    fn pack_config_encoding() {
+        assert_eq!(
+            encode_pack_config::<()>(
+                4,
+                2,
+                ThermistorType::Ntc100KOhm,
+                ChargePumpVoltageConfiguration::Cp10V,
+                AlwaysOnRegulatorConfiguration::Enabled3p4V,
+                BatteryPackUpdate::AfterMeasurementsCompleted,
+            ),
+            Ok(0x6A0A)
+        );
+        assert_eq!(
+            encode_pack_config::<()>(
+                3,
+                4,
+                ThermistorType::Ntc10KOhm,
+                ChargePumpVoltageConfiguration::Cp8V,
+                AlwaysOnRegulatorConfiguration::Enabled1p8V,
+                BatteryPackUpdate::UpdateEvery22p4s,
+            ),
+            Ok(0x8111)
+        );
+        assert_eq!(pack_config(2, 0), Ok(0x0000));
+    }
+
+    #[test]
+    fn pack_config_bounds() {
+        assert_eq!(pack_config(1, 0), Err(Error::UnsupportedCellCount(1)));
+        assert_eq!(pack_config(5, 0), Err(Error::UnsupportedCellCount(5)));
+        assert_eq!(pack_config(4, 5), Err(Error::InvalidConfigurationValue(5)));
+    }
+
+    #[test]
+    fn capacity_projection() {
+        assert_eq!(project_capacity(1000.0, 25.0, 40.0), 1000.0);
+        assert!((project_capacity(1000.0, 25.0, 0.0) - 850.0).abs() < 1e-3);
+        assert!((project_capacity(850.0, 0.0, 25.0) - 1000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn thermistor_channels_must_be_a_prefix() {
+        use ThermChannelRole::*;
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.nvm[RegisterNvm::NPackCfg as usize] = 0x0002;
+        bat.set_thermistor_channel_config([Protection, Protection, Disabled, Disabled])
+            .unwrap();
+        assert_eq!(bat.com.nvm[RegisterNvm::NPackCfg as usize], 0x000A);
+        assert_eq!(
+            bat.read_thermistor_channel_config(),
+            Ok([Protection, Protection, Disabled, Disabled])
+        );
+        assert_eq!(
+            bat.set_thermistor_channel_config([Protection, Disabled, Protection, Disabled]),
+            Err(Error::InvalidConfigurationValue(3))
+        );
+    }
+
+    #[test]
+    fn nvm_address_errors_are_distinguished() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.fail = true;
+        assert_eq!(
+            bat.read_battery_status(),
+            Err(Error::NvmAddressUnreachable(()))
+        );
+        assert_eq!(bat.read_status(), Err(Error::BusError(())));
+    }
+
+    #[test]
+    fn capacity_lsb_matches_datasheet() {
+        // Datasheet capacity LSB table: 0.5mAh at 10mΩ, full scale 32767.5mAh
+        assert_eq!(convert_to_capacity(1, 10.0), 0.5);
+        assert_eq!(convert_to_capacity(0xFFFF, 10.0), 32767.5);
+        // 5mΩ doubles the LSB, 1mΩ is 5mAh
+        assert_eq!(convert_to_capacity(1000, 5.0), 1000.0);
+        assert_eq!(convert_to_capacity(1, 1.0), 5.0);
+    }
+
+    #[test]
+    fn cumulative_energy_splits_charge_and_discharge() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.main[Register::VFOcv as usize] = 51200; // 4V per cell
+        bat.com.nvm[RegisterNvm::NPackCfg as usize] = 0x0000; // 2S
+        bat.com.main[Register::QH as usize] = 0;
+        assert_eq!(
+            bat.read_cumulative_energy(),
+            Ok(CumulativeEnergy::default())
+        );
+        bat.com.main[Register::QH as usize] = 1000; // +1000mAh
+        bat.read_cumulative_energy().unwrap();
+        bat.com.main[Register::QH as usize] = 500; // -500mAh
+        let energy = bat.read_cumulative_energy().unwrap();
+        assert!((energy.charged_wh - 8.0).abs() < 1e-3);
+        assert!((energy.discharged_wh - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn batched_alert_thresholds_validate_first() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        let thresholds = AlertThresholds {
+            voltage_v: (3.0, 4.2),
+            temperature_c: (-10, 60),
+            soc_percent: (5, 95),
+            current: (-100, 100),
+        };
+        bat.set_all_alert_thresholds(thresholds).unwrap();
+        assert_eq!(bat.read_state_of_charge_alert_threshold(), Ok((5, 95)));
+        assert_eq!(bat.read_current_alert_threshold(), Ok((-100, 100)));
+
+        let inverted = AlertThresholds {
+            soc_percent: (95, 5),
+            temperature_c: (0, 45),
+            ..thresholds
+        };
+        assert_eq!(
+            bat.set_all_alert_thresholds(inverted),
+            Err(Error::InvalidAlertThreshold(AlertThresholdField::Soc))
+        );
+        // Nothing was written
+        assert_eq!(bat.read_temperature_alert_threshold(), Ok((-10, 60)));
+    }
+
+    #[test]
+    fn averaged_current_needs_samples() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.main[Register::Current as usize] = 32; // 10mA
+        assert_eq!(bat.read_current_averaged(5), Ok(10.0));
+        assert_eq!(
+            bat.read_current_averaged(0),
+            Err(Error::InvalidConfigurationValue(0))
+        );
+    }
+
     #[test]
     fn valid_voltage_threshold() {
-        assert!(is_valid_voltage_threshold(5.1))
+        assert!(is_valid_voltage_threshold(5.1));
+        assert!(is_valid_voltage_threshold(4.2));
+        assert_eq!(voltage_threshold_code(4.2), 210);
+        assert!(!is_valid_voltage_threshold(4.21));
+    }
+
+    #[test]
+    fn cell_sum_matches_pack() {
+        let cells = [3.7, 3.71, 3.69];
+        assert!(cells_agree_with_pack(&cells, 11.1, 0.05));
+        assert!(!cells_agree_with_pack(&cells[..2], 11.1, 0.05));
+    }
+
+    #[test]
+    fn alrt_pin_sources_skip_disabled_thresholds() {
+        let disabled = ALERT_THRESHOLD_DISABLED;
+        let sources = alrt_pin_sources(1 << 2, 1 << 7, [0xD2A0, 0x3C80, 0xFF00, 0x7F80]);
+        assert!(sources.pin_enabled && sources.voltage && sources.soc_change);
+        assert!(!sources.temperature && !sources.soc && !sources.current);
+
+        let sources = alrt_pin_sources(0, 0xFFFF, [0xD2A0; 4]);
+        assert_eq!(sources, alrt_pin_sources(0, 0, disabled));
+        assert!(!sources.pin_enabled && !sources.voltage && !sources.soc_change);
+    }
+
+    #[test]
+    fn pack_connection_from_voltages_and_current() {
+        use PackConnection::*;
+        assert_eq!(classify_pack_connection(12.0, 12.0, -250.0), Connected);
+        assert_eq!(classify_pack_connection(12.0, 11.98, 1.0), Disconnected);
+        assert_eq!(classify_pack_connection(12.0, 12.0, 500.0), ChargerPresent);
+        assert_eq!(classify_pack_connection(12.0, 12.6, 0.0), ChargerPresent);
+    }
+
+    #[test]
+    fn runtime_projection_derates_in_the_cold() {
+        let mut i2c = MockI2c::new();
+        // The gauge projects 2h at the programmed rate, at 25°C
+        i2c.main[Register::AtTte as usize] = 1280;
+        i2c.main[Register::Temp as usize] = 25 * 256;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        assert_eq!(
+            bat.read_runtime_projection(1000.0, 25.0).unwrap(),
+            Duration::from_secs(7200)
+        );
+        // 1000mA discharge with a 5mΩ sense resistor
+        assert_eq!(bat.com.main[Register::AtRate as usize], -3200i16 as u16);
+        let cold = bat.read_runtime_projection(1000.0, -5.0).unwrap();
+        assert!((cold.as_secs_f32() - 7200.0 * 0.82).abs() < 1.0);
+        for load_ma in [0.0, -100.0, f32::NAN, f32::INFINITY, 1e-36, 20_000.0] {
+            assert_eq!(
+                bat.read_runtime_projection(load_ma, 25.0),
+                Err(Error::InvalidLoadCurrent)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_voltage_round_trip() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.set_empty_voltage(3.0, 3.88).unwrap();
+        assert_eq!(bat.com.main[Register::VEmpty as usize], 300 << 7 | 97);
+        let limits = bat.read_operating_voltage_limits().unwrap();
+        assert!((limits.empty_v - 3.0).abs() < 1e-3);
+        assert!((limits.recovery_v - 3.88).abs() < 1e-3);
+        assert_eq!(
+            bat.set_empty_voltage(3.0, 5.2),
+            Err(Error::InvalidConfigurationValue(5200))
+        );
+    }
+
+    #[test]
+    fn pack_voltages_use_pack_lsb() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.main[Register::Batt as usize] = 25600; // 8V
+        bat.com.main[Register::Pckp as usize] = 0xFFFF;
+        assert_eq!(bat.read_batt(), Ok(8.0));
+        assert!((bat.read_pckp().unwrap() - 20.48).abs() < 1e-3);
+        // The per-cell registers keep the finer 0.078125mV LSB
+        bat.com.main[Register::Cell1 as usize] = 25600;
+        assert_eq!(bat.read_cell1(), Ok(2.0));
+    }
+
+    #[test]
+    fn protector_temperature_reads_secondary_page_signed() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.com.nvm[RegisterSecondary::Temp2 as usize] = (-10i16 * 256) as u16;
+        assert_eq!(bat.read_protector_temperature(2), Ok(-10.0));
+        assert!(bat.read_protector_temperature(5).is_err());
+    }
+
+    #[test]
+    fn capacity_overflow_detail_uses_coulomb_counter() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        assert_eq!(bat.read_capacity_overflow_detail(), Ok(None));
+
+        bat.com.main[Register::ProtStatus as usize] = ProtStatusCode::CapacityOverflow as u16;
+        bat.com.main[Register::QH as usize] = 3300;
+        bat.com.main[Register::RepCap as usize] = 3000;
+        bat.com.main[Register::FullCapRep as usize] = 2800;
+        bat.com.nvm[RegisterNvm::NDesignCap as usize] = 3000;
+        let detail = bat.read_capacity_overflow_detail().unwrap().unwrap();
+        assert_eq!(detail.accumulated_mah, 3300.0);
+        assert_eq!(detail.overflow_mah(), 300.0);
+    }
+
+    #[test]
+    fn configure_batches_writes_in_one_unlock() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.configure(|bat| {
+            bat.set_protection_config2(ProtectionConfig2::from_bits(0x001F))?;
+            bat.set_charge_watchdog_timeout(Some(Duration::from_secs(1800)))?;
+            bat.set_measurement_period(Duration::from_secs(3))
+        })
+        .unwrap();
+        // One unlock (double write of 0) and one lock (double write of 0xF9)
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+
+        bat.com.comm_stat_writes.clear();
+        bat.set_measurement_period(Duration::from_secs(3)).unwrap();
+        bat.set_measurement_period(Duration::from_secs(3)).unwrap();
+        assert_eq!(bat.com.comm_stat_writes.len(), 8);
+        // The live HibCfg is written, not the nHibCfg shadow
+        assert_eq!(bat.com.main[Register::HibCfg as usize], 3);
+        assert_eq!(bat.com.nvm[RegisterNvm::NHibCfg as usize], 0);
+        assert_eq!(bat.read_measurement_period().unwrap(), hibernate_period(3));
+    }
+
+    #[test]
+    fn charge_watchdog_timeout_round_trip() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm[RegisterNvm::NDelayCfg as usize] = 0x0155;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        assert_eq!(bat.read_charge_watchdog_timeout().unwrap(), None);
+
+        bat.set_charge_watchdog_timeout(Some(Duration::from_secs(3600)))
+            .unwrap();
+        assert_eq!(bat.com.nvm[RegisterNvm::NDelayCfg as usize], 0x8155);
+        assert_eq!(
+            bat.read_charge_watchdog_timeout().unwrap(),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            bat.set_charge_watchdog_timeout(Some(Duration::from_secs(20 * 60))),
+            Err(Error::InvalidConfigurationValue(20))
+        );
+
+        bat.set_charge_watchdog_timeout(None).unwrap();
+        assert_eq!(bat.read_charge_watchdog_timeout().unwrap(), None);
+    }
+
+    #[test]
+    fn fet_resistance_from_drop_and_current() {
+        let drop = fet_voltage_drop(12.02, 12.0, -2000.0);
+        assert!((drop.drop_v - 0.02).abs() < 1e-4);
+        assert!((drop.resistance_mohm.unwrap() - 10.0).abs() < 0.1);
+        assert_eq!(fet_voltage_drop(12.0, 12.0, 1.0).resistance_mohm, None);
+    }
+
+    #[test]
+    fn full_charge_recorded_on_rising_edge() {
+        let mut i2c = MockI2c::new();
+        i2c.main[Register::Cycles as usize] = 1250; // 200 cycles
+        i2c.main[Register::FullCapRep as usize] = 3000;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        assert_eq!(bat.read_last_full_charge_info().unwrap(), None);
+
+        bat.com.main[Register::ProtStatus as usize] = ProtStatusCode::Full as u16;
+        assert!(bat.is_fully_charged().unwrap());
+        bat.com.main[Register::Cycles as usize] = 1300;
+        let info = bat.read_last_full_charge_info().unwrap().unwrap();
+        assert!((info.cycles - 200.0).abs() < 1e-3);
+        assert_eq!(info.full_capacity_mah, 3000.0);
+    }
+
+    #[test]
+    fn recommended_charge_voltage_scales_to_pack() {
+        let mut i2c = MockI2c::new();
+        // 4.2V per cell, 3S
+        i2c.main[Register::ChargingVoltage as usize] = 53760;
+        i2c.nvm[RegisterNvm::NPackCfg as usize] = 0x0001;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let (cell_v, pack_v) = bat.read_recommended_charge_voltage().unwrap();
+        assert!((cell_v - 4.2).abs() < 1e-4);
+        assert!((pack_v - 12.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn poll_alerts_decodes_and_clears_gauge_bits() {
+        let mut i2c = MockI2c::new();
+        i2c.main[Register::Status as usize] = StatusCode::PowerOnReset as u16;
+        i2c.main[Register::VCell as usize] = 38400;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        assert_eq!(bat.poll_alerts().unwrap(), None);
+
+        bat.com.main[Register::Status as usize] |=
+            StatusCode::MaxVoltageExceeded as u16 | StatusCode::ProtectionAlert as u16;
+        bat.com.main[Register::ProtAlrt as usize] = ProtStatusCode::Undervoltage as u16;
+        let report = bat.poll_alerts().unwrap().unwrap();
+        assert_eq!(report.protection_alert, Some(0x0008));
+        assert!((report.cell_voltage_v.unwrap() - 3.0).abs() < 1e-4);
+        assert_eq!(report.temperature_c, None);
+        assert_eq!(
+            bat.com.main[Register::Status as usize],
+            StatusCode::PowerOnReset as u16 | StatusCode::ProtectionAlert as u16
+        );
+    }
+
+    #[test]
+    fn body_diode_follows_current_direction() {
+        use BodyDiodeConduction::*;
+        assert_eq!(body_diode_conduction(true, false, 300.0), DischargeFet);
+        assert_eq!(body_diode_conduction(true, false, -300.0), None);
+        assert_eq!(body_diode_conduction(false, true, -300.0), ChargeFet);
+        assert_eq!(body_diode_conduction(false, false, 1.0), None);
+        assert_eq!(body_diode_conduction(true, true, 300.0), None);
+    }
+
+    #[test]
+    fn hibernate_period_rounds_to_nearest_step() {
+        assert_eq!(hibernate_period(0), Duration::from_micros(351_500));
+        assert_eq!(hibernate_period(7), Duration::from_micros(44_992_000));
+        assert_eq!(hib_scalar_for(Duration::from_millis(350)), Some(0));
+        assert_eq!(hib_scalar_for(Duration::from_millis(1500)), Some(2));
+        assert_eq!(hib_scalar_for(Duration::from_secs(60)), Some(7));
+        assert_eq!(hib_scalar_for(Duration::from_millis(10)), Some(0));
+        assert_eq!(hib_scalar_for(Duration::ZERO), None);
+        assert_eq!(hib_scalar_for(Duration::from_secs(100)), None);
+    }
+
+    #[test]
+    fn nvm_write_refused_while_protected() {
+        let mut bat = MAX17320::new(MockI2c::new(), 5.0).unwrap();
+        bat.lock_write_protection().unwrap();
+        assert_eq!(
+            bat.write_nvm_checked(RegisterNvm::NIChgTerm, 0x0640),
+            Err(Error::WriteProtected)
+        );
+        assert_eq!(bat.com.nvm[RegisterNvm::NIChgTerm as usize], 0);
+
+        bat.unlock_write_protection().unwrap();
+        bat.write_nvm_checked(RegisterNvm::NIChgTerm, 0x0640)
+            .unwrap();
+        assert_eq!(bat.com.nvm[RegisterNvm::NIChgTerm as usize], 0x0640);
+    }
+
+    #[cfg(feature = "permanent-fail-clear")]
+    #[test]
+    fn clear_permanent_fail_latch_relocks() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm[RegisterNvm::NBattStatus as usize] = 0x0200;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        assert_eq!(bat.read_permanent_fail_latch(), Ok(0x0200));
+        bat.clear_permanent_fail_latch().unwrap();
+        assert_eq!(bat.read_permanent_fail_latch(), Ok(0));
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+
+        bat.com.comm_stat_writes.clear();
+        bat.com.fail_nvm_writes = true;
+        assert!(bat.clear_permanent_fail_latch().is_err());
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn register_map_round_trip() {
+        let mut i2c = MockI2c::new();
+        i2c.nvm[0xB5] = 0x3A03;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let mut map = bat.read_register_map().unwrap();
+        assert_eq!(map.registers.len(), 0x60);
+        assert_eq!(map.registers[&0x1B5], 0x3A03);
+
+        map.registers.insert(0x1B5, 0x3A02);
+        bat.apply_register_map(&map).unwrap();
+        assert_eq!(bat.com.nvm[0xB5], 0x3A02);
+
+        map.registers.insert(0x1E0, 0);
+        assert_eq!(
+            bat.apply_register_map(&map),
+            Err(Error::InvalidConfigurationValue(0x1E0))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn register_map_relocks_after_bus_error() {
+        let mut i2c = MockI2c::new();
+        i2c.fail_nvm_writes = true;
+        let mut bat = MAX17320::new(i2c, 5.0).unwrap();
+        let mut map = RegisterMap::default();
+        map.registers.insert(0x1B5, 0x3A02);
+        assert!(bat.apply_register_map(&map).is_err());
+        assert_eq!(bat.com.comm_stat_writes, [0, 0, 0xF9, 0xF9]);
     }
 }
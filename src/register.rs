@@ -1,44 +1,217 @@
+// Register addresses are the enum discriminants, so rustc rejects a duplicate
+// address within Register or within RegisterNvm at compile time (E0081). The
+// same address in both enums is expected: they are different I2C pages.
+
+/// Registers at the main I2C address, named as in the datasheet
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Register {
+    /// Device name and revision (21h)
     DevName = 0x21,
+    /// Alert and status flags (00h)
     Status = 0x00,
+    /// Reported remaining capacity (05h)
     RepCap = 0x05,
+    /// Reported state of charge (06h)
     RepSoc = 0x06,
+    /// Cell voltage (1Ah)
     VCell = 0x1A,
+    /// Temperature (1Bh)
     Temp = 0x1B,
+    /// Instantaneous current (1Ch)
     Current = 0x1C,
+    /// Averaged current (1Dh)
+    AvgCurrent = 0x1D,
+    /// Estimated time to empty (11h)
     TimeToEmpty = 0x11,
+    /// Estimated time to full (20h)
     TimeToFull = 0x20,
+    /// Active protection faults (D9h)
     ProtStatus = 0xD9,
+    /// Latched protection fault history (AFh)
     ProtAlrt = 0xAF,
+    /// Communication status, write protection and FET overrides (61h)
     CommStat = 0x61,
+    /// Cell 1 voltage (D8h)
     Cell1 = 0xD8,
+    /// Cell 2 voltage (D7h)
     Cell2 = 0xD7,
+    /// Cell 3 voltage (D6h)
     Cell3 = 0xD6,
+    /// Cell 4 voltage (D5h)
     Cell4 = 0xD5,
+    /// Total pack voltage inside the protector (DAh)
     Batt = 0xDA,
+    /// PACK+ voltage (DBh)
     Pckp = 0xDB,
+    /// Die temperature (34h)
     DieTemp = 0x34,
+    /// Alert and operating configuration (0Bh)
     Config = 0x0B,
+    /// Secondary configuration (ABh)
     Config2 = 0xAB,
+    /// Voltage alert thresholds (01h)
     VAlrtTh = 0x01,
+    /// Temperature alert thresholds (02h)
     TAlrtTh = 0x02,
+    /// State of charge alert thresholds (03h)
     SAlrtTh = 0x03,
+    /// Current alert thresholds (ACh)
     IAlrtTh = 0xAC,
+    /// Estimated cycles remaining (B9h)
     AgeForecast = 0xB9,
+    /// Full capacity as a percentage of design capacity (07h)
     Age = 0x07,
+    /// Charge cycle counter (17h)
     Cycles = 0x17,
+    /// Cell internal resistance (14h)
     RCell = 0x14,
+    /// Protection state machine status (D0h)
+    FProtStat = 0xD0,
+    /// Command register (60h)
+    Command = 0x60,
+    /// Fuel gauge status flags (3Dh)
+    FStat = 0x3D,
+    /// Elapsed time, low word (3Eh)
+    Timer = 0x3E,
+    /// Elapsed time, high word (BEh)
+    TimerH = 0xBE,
+    /// Remaining capacity before empty compensation (0Fh)
+    MixCap = 0x0F,
+    /// Available remaining capacity (1Fh)
+    AvCap = 0x1F,
+    /// Reported full capacity (10h)
+    FullCapRep = 0x10,
+    /// Nominal full capacity (23h)
+    FullCapNom = 0x23,
+    /// Charge accumulated between model updates (45h)
+    DQAcc = 0x45,
+    /// SOC change accumulated between model updates (46h)
+    DPAcc = 0x46,
+    /// Cell model configuration and refresh (A3h)
+    ModelCfg = 0xA3,
+    /// Estimated open circuit voltage (FBh)
+    VFOcv = 0xFB,
+    /// Empty and recovery voltages (3Ah)
+    VEmpty = 0x3A,
+    /// Hibernate mode configuration (BAh)
+    HibCfg = 0xBA,
+    /// Averaged cell voltage (19h)
+    AvgVCell = 0x19,
+    /// Averaged cell 1 voltage (D4h)
+    AvgCell1 = 0xD4,
+    /// Averaged cell 2 voltage (D3h)
+    AvgCell2 = 0xD3,
+    /// Averaged cell 3 voltage (D2h)
+    AvgCell3 = 0xD2,
+    /// Averaged cell 4 voltage (D1h)
+    AvgCell4 = 0xD1,
+    /// Current measurement gain (2Eh)
+    CGain = 0x2E,
+    /// Current measurement offset (2Fh)
+    COff = 0x2F,
+    /// Coulomb counter (4Dh)
+    QH = 0x4D,
+    /// Characterization resistance at 25°C (38h)
+    RComp0 = 0x38,
+    /// Characterization temperature compensation (39h)
+    TempCo = 0x39,
+    /// State of charge before empty compensation (0Dh)
+    MixSoc = 0x0D,
+    /// Voltage fuel gauge state of charge (FFh)
+    VFSoc = 0xFF,
+    /// Recommended charge current (28h)
+    ChargingCurrent = 0x28,
+    /// Recommended charge voltage (2Ah)
+    ChargingVoltage = 0x2A,
+    /// Load current for the at-rate estimates (04h)
+    AtRate = 0x04,
+    /// Time to empty at the AtRate load (DDh)
+    AtTte = 0xDD,
 }
 
+/// Nonvolatile shadow registers at the NVM I2C address, addressed by the low
+/// byte of their 1xxh address
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RegisterNvm {
+    /// Permanent failure latch (1A8h)
     NBattStatus = 0xA8,
+    /// Pack configuration (1B5h)
     NPackCfg = 0xB5,
+    /// Power-on configuration (1B0h)
     NConfig = 0xB0,
+    /// Power-on voltage alert thresholds (18Ch)
     NVAlrtTh = 0x8C,
+    /// Power-on temperature alert thresholds (18Dh)
     NTAlrtTh = 0x8D,
+    /// Power-on state of charge alert thresholds (18Fh)
     NSAlrtTh = 0x8F,
+    /// Power-on current alert thresholds (18Eh)
     NIAlrtTh = 0x8E,
+    /// Lifetime maximum and minimum current (1ABh)
+    NMaxMinCurr = 0xAB,
+    /// Lifetime maximum and minimum voltage (1ACh)
+    NMaxMinVolt = 0xAC,
+    /// Lifetime maximum and minimum temperature (1ADh)
+    NMaxMinTemp = 0xAD,
+    /// Lifetime protection fault log (1AEh)
+    NFaultLog = 0xAE,
+    /// First entry of the open circuit voltage table (190h)
+    NOcvTable0 = 0x90,
+    /// Time to full configuration (1C7h)
+    NTtfCfg = 0xC7,
+    /// Design capacity (1B3h)
+    NDesignCap = 0xB3,
+    /// Thermistor curve correction (1C9h)
+    NTCurve = 0xC9,
+    /// Thermistor gain (1CAh)
+    NTGain = 0xCA,
+    /// Thermistor offset (1CBh)
+    NTOff = 0xCB,
+    /// First word of the manufacturer name (1CCh)
+    NManfctrName0 = 0xCC,
+    /// Manufacture date (1CFh)
+    NManfctrDate = 0xCF,
+    /// First word of the serial number (1E0h)
+    NSerialNumber0 = 0xE0,
+    /// NVM configuration 0 (1B8h)
+    NNvCfg0 = 0xB8,
+    /// NVM configuration 1 (1B9h)
+    NNvCfg1 = 0xB9,
+    /// NVM configuration 2 (1BAh)
+    NNvCfg2 = 0xBA,
+    /// Protection configuration (1D7h)
+    NProtCfg = 0xD7,
+    /// Optional protection feature enables (1DFh)
+    NProtCfg2 = 0xDF,
+    /// Undervoltage protection thresholds (1D0h)
+    NUvPrtTh = 0xD0,
+    /// Temperature protection thresholds 3 (1D2h)
+    NTPrtTh3 = 0xD2,
+    /// Averaging filter configuration (19Dh)
+    NFilterCfg = 0x9D,
+    /// Power-on hibernate mode configuration (1B4h)
+    NHibCfg = 0xB4,
+    /// Protection timers (1DCh)
+    NDelayCfg = 0xDC,
+    /// Charge termination current (19Ch)
+    NIChgTerm = 0x9C,
+    /// Used NVM write cycles (1EDh)
+    NRemainingUpdates = 0xED,
+}
+
+/// Volatile registers that live on the secondary (NVM) I2C address, addressed by
+/// the low byte of their 0x1xx address
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RegisterSecondary {
+    /// Thermistor TH4 temperature (0x137)
+    Temp4 = 0x37,
+    /// Thermistor TH3 temperature (0x138)
+    Temp3 = 0x38,
+    /// Thermistor TH2 temperature (0x139)
+    Temp2 = 0x39,
+    /// Thermistor TH1 temperature (0x13A)
+    Temp1 = 0x3A,
 }
 
 /// All flags contained within the status register
@@ -103,6 +276,7 @@ pub enum StatusCode {
     ProtectionAlert = 0b1000_0000_0000_0000,
 }
 /// All fault states of the protection state machine
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ProtStatusCode {
     /// Flag to indicate ship state
     Ship = 0b0000_0000_0000_0001,
@@ -174,6 +348,17 @@ pub enum ProtAlertCode {
     ChargeWatchDogTimer = 0b1000_0000_0000_0000,
 }
 
+/// Commands accepted by the Command register
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CommandCode {
+    /// Copy the nonvolatile shadow RAM into NVM, consuming one write cycle
+    CopyNvBlock = 0xE904,
+    /// Recall the NVM contents into shadow RAM
+    NvRecall = 0xE001,
+    /// Load the NVM write history into nRemainingUpdates
+    RecallRemainingUpdates = 0xE2FA,
+}
+
 pub enum CommStatCode {
     /// Set this bit to 1 to forcefully turn off DIS FET ignoring
     /// all other conditions if nProtCfg.CmOvrdEn is enabled.
@@ -213,6 +398,40 @@ pub enum CommStatCode {
     WriteProtectionGlobal = 1,
 }
 
+/// Fuel gauge status flags in the FStat register
+pub enum FStatCode {
+    /// Data Not Ready. Set at power-up until the first measurements are complete.
+    DataNotReady = 1,
+}
+
+/// Fields of the ModelCfg register
+pub enum ModelCfgCode {
+    /// Set to reload the model; cleared by the gauge once it has been processed
+    Refresh = 1 << 15,
+    /// Set when a 100kΩ NTC thermistor is used
+    R100 = 1 << 13,
+}
+
+/// Actual gate-drive state of the protection FETs
+pub enum FProtStatCode {
+    /// DIS FET gate is being driven on
+    DischargeFetOn = 1 << 9,
+    /// CHG FET gate is being driven on
+    ChargeFetOn = 1 << 8,
+}
+
+/// Global write protection plus WP1-WP5
+pub(crate) const WRITE_PROTECTION_BITS: u16 = CommStatCode::WriteProtectionGlobal as u16
+    | CommStatCode::WriteProtection1 as u16
+    | CommStatCode::WriteProtection2 as u16
+    | CommStatCode::WriteProtection3 as u16
+    | CommStatCode::WriteProtection4 as u16
+    | CommStatCode::WriteProtection5 as u16;
+
+/// CHGOff and DISOff
+pub(crate) const FET_OVERRIDE_BITS: u16 =
+    CommStatCode::DischargeOff as u16 | CommStatCode::ChargeOff as u16;
+
 pub fn has_code(look_for: u16, within: u16) -> bool {
     (look_for & within) > 0
 }
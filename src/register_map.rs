@@ -0,0 +1,65 @@
+//! Whole NVM register map export and import for host side configuration tools.
+
+use super::*;
+use crate::i2c_interface::MAX_BLOCK_WORDS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// First user nonvolatile shadow register
+const MAP_FIRST: u16 = 0x180;
+/// Last user nonvolatile shadow register (0x1DF). 0x1E0 onwards holds the
+/// serial number, ROM ID and remaining update counters, which are read-only.
+const MAP_LAST: u16 = 0x1DF;
+
+/// The user nonvolatile configuration (shadow RAM 0x180 to 0x1DF), keyed by the
+/// datasheet register address.
+///
+/// Produced by read_register_map and written back by apply_register_map. JSON
+/// encodes the keys as decimal strings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterMap {
+    /// Register value by datasheet address
+    pub registers: BTreeMap<u16, u16>,
+}
+
+impl<I2C, D, E> MAX17320<I2C, D>
+where
+    I2C: WriteRead<Error = E> + Write<Error = E> + Read<Error = E>,
+    D: DelayUs<u32>,
+{
+    /// Read every user nonvolatile shadow register into a RegisterMap
+    pub fn read_register_map(&mut self) -> Result<RegisterMap, Error<E>> {
+        let mut registers = BTreeMap::new();
+        let mut block = [0u16; MAX_BLOCK_WORDS];
+        for start in (MAP_FIRST..=MAP_LAST).step_by(MAX_BLOCK_WORDS) {
+            self.read_block(start as u8, self.address_nvm, &mut block)?;
+            for (i, code) in block.iter().enumerate() {
+                registers.insert(start + i as u16, *code);
+            }
+        }
+        Ok(RegisterMap { registers })
+    }
+
+    /// Write a RegisterMap into the nonvolatile shadow RAM.
+    ///
+    /// Every address is checked before anything is written; an address outside
+    /// 0x180 to 0x1DF returns Error::InvalidConfigurationValue(address). The new
+    /// values take effect immediately but are lost on reset until
+    /// commit_nonvolatile_memory is called. Write protection is restored even if
+    /// a write fails partway through.
+    pub fn apply_register_map(&mut self, map: &RegisterMap) -> Result<(), Error<E>> {
+        if let Some(address) = map
+            .registers
+            .keys()
+            .find(|address| !(MAP_FIRST..=MAP_LAST).contains(*address))
+        {
+            return Err(Error::InvalidConfigurationValue(*address));
+        }
+        self.configure(|bat| {
+            for (address, code) in &map.registers {
+                bat.write_nvm_address(*address as u8, *code)?;
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,157 @@
+//! Command-and-poll sequences written as state machines that do no I/O, so the
+//! blocking and async drivers share the same logic and only differ in how they
+//! perform each Action.
+
+use crate::error::Error;
+use crate::register::*;
+
+/// Largest number of polls before a sequence gives up with Error::Timeout
+pub(crate) const MAX_LOOP: u16 = 500;
+
+/// Wait between CommStat.NVBusy polls. MAX_LOOP polls at this interval cover
+/// the worst case NVM block copy time (tBLOCK, 7360ms).
+const NVM_POLL_INTERVAL_US: u32 = 16_000;
+
+/// Time for an NV recall to repopulate the shadow RAM (tRECALL)
+const T_RECALL_US: u32 = 5_000;
+
+/// Wait between ModelCfg.Refresh polls
+const MODEL_REFRESH_POLL_INTERVAL_US: u32 = 10_000;
+
+/// Bus operation requested by a sequence
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Action {
+    /// Write a register at the main address
+    Write(Register, u16),
+    /// Read a register at the main address and pass the value to the next step
+    Read(Register),
+    /// Wait this many µs
+    Delay(u32),
+    /// The sequence completed successfully
+    Done,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum NvmState {
+    Send(CommandCode),
+    Settle,
+    Poll,
+    CheckBusy,
+    CheckError,
+}
+
+/// Send an optional nonvolatile memory command, then poll CommStat.NVBusy and
+/// check CommStat.NVError.
+///
+/// Minimum waits, enforced when the driver has a delay:
+/// - CopyNvBlock: polled every 16ms for up to tBLOCK (7360ms)
+/// - NvRecall: tRECALL (5ms) before the shadow RAM is read back
+#[derive(Debug)]
+pub(crate) struct NvmSequence {
+    settle_us: u32,
+    reg: Option<RegisterNvm>,
+    state: NvmState,
+    polls: u16,
+}
+
+impl NvmSequence {
+    /// Send command and wait for it to finish. reg is only used to identify the
+    /// failing register in the returned error; without one (block copies) a
+    /// failure is reported as Error::NvmCopyFailed.
+    pub(crate) fn command(command: CommandCode, reg: Option<RegisterNvm>) -> Self {
+        let settle_us = match command {
+            CommandCode::NvRecall | CommandCode::RecallRemainingUpdates => T_RECALL_US,
+            CommandCode::CopyNvBlock => 0,
+        };
+        Self {
+            settle_us,
+            reg,
+            state: NvmState::Send(command),
+            polls: 0,
+        }
+    }
+
+    /// Wait for an NVM operation started by a shadow register write to finish
+    pub(crate) fn wait(reg: RegisterNvm) -> Self {
+        Self {
+            settle_us: 0,
+            reg: Some(reg),
+            state: NvmState::Poll,
+            polls: 0,
+        }
+    }
+
+    /// Next action to perform. read is the value returned by the previous
+    /// Action::Read and is ignored otherwise.
+    pub(crate) fn step<E>(&mut self, read: u16) -> Result<Action, Error<E>> {
+        match self.state {
+            NvmState::Send(command) => {
+                self.state = NvmState::Settle;
+                Ok(Action::Write(Register::Command, command as u16))
+            }
+            NvmState::Settle => {
+                self.state = NvmState::Poll;
+                Ok(Action::Delay(self.settle_us))
+            }
+            NvmState::Poll => {
+                self.state = NvmState::CheckBusy;
+                Ok(Action::Read(Register::CommStat))
+            }
+            NvmState::CheckBusy => {
+                if !has_code(CommStatCode::NonvolatileBusy as u16, read) {
+                    self.state = NvmState::CheckError;
+                    return Ok(Action::Read(Register::CommStat));
+                }
+                self.polls += 1;
+                if self.polls == MAX_LOOP {
+                    return Err(Error::Timeout);
+                }
+                self.state = NvmState::Poll;
+                Ok(Action::Delay(NVM_POLL_INTERVAL_US))
+            }
+            NvmState::CheckError => {
+                if has_code(CommStatCode::NonvolatileError as u16, read) {
+                    return Err(match self.reg {
+                        Some(reg) => Error::NonvolatileError(reg),
+                        None => Error::NvmCopyFailed,
+                    });
+                }
+                Ok(Action::Done)
+            }
+        }
+    }
+}
+
+/// Poll until the gauge clears ModelCfg.Refresh, or Error::Timeout
+#[derive(Debug)]
+pub(crate) struct ModelRefreshSequence {
+    polled: bool,
+    polls: u16,
+}
+
+impl ModelRefreshSequence {
+    pub(crate) fn new() -> Self {
+        Self {
+            polled: false,
+            polls: 0,
+        }
+    }
+
+    /// Next action to perform. read is the value returned by the previous
+    /// Action::Read and is ignored otherwise.
+    pub(crate) fn step<E>(&mut self, read: u16) -> Result<Action, Error<E>> {
+        if !self.polled {
+            self.polled = true;
+            return Ok(Action::Read(Register::ModelCfg));
+        }
+        self.polled = false;
+        if !has_code(ModelCfgCode::Refresh as u16, read) {
+            return Ok(Action::Done);
+        }
+        self.polls += 1;
+        if self.polls == MAX_LOOP {
+            return Err(Error::Timeout);
+        }
+        Ok(Action::Delay(MODEL_REFRESH_POLL_INTERVAL_US))
+    }
+}
@@ -0,0 +1,487 @@
+/// Snapshot of whether the analog front end is producing measurements
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MeasurementStatus {
+    /// FStat.DNR is clear, i.e. the first measurements after reset are complete
+    pub data_ready: bool,
+    /// Raw Timer register. It advances every 175.8ms while the gauge is running,
+    /// so a value that stops changing between reads indicates a stuck gauge.
+    pub timer: u16,
+}
+
+/// Lifetime record of worst-case conditions and protection faults stored in NVM
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProtectionHistory {
+    /// Highest recorded temperature (°C)
+    pub max_temperature: i8,
+    /// Lowest recorded temperature (°C)
+    pub min_temperature: i8,
+    /// Highest recorded cell voltage (V)
+    pub max_voltage: f32,
+    /// Lowest recorded cell voltage (V)
+    pub min_voltage: f32,
+    /// Highest recorded current (mA)
+    pub max_current: f32,
+    /// Lowest recorded current (mA)
+    pub min_current: f32,
+    /// Every protection fault that has ever tripped, using the ProtAlertCode bit layout
+    pub fault_flags: u16,
+}
+
+impl ProtectionHistory {
+    /// Decode the nMaxMinTemp, nMaxMinVolt, nMaxMinCurr and nFaultLog registers.
+    /// r_sense is in mΩ.
+    pub fn from_registers(
+        max_min_temp: u16,
+        max_min_volt: u16,
+        max_min_curr: u16,
+        fault_log: u16,
+        r_sense: f32,
+    ) -> Self {
+        let temp = max_min_temp.to_be_bytes();
+        let volt = max_min_volt.to_be_bytes();
+        let curr = max_min_curr.to_be_bytes();
+        Self {
+            max_temperature: temp[0] as i8,
+            min_temperature: temp[1] as i8,
+            max_voltage: volt[0] as f32 * MAX_MIN_VOLT_LSB,
+            min_voltage: volt[1] as f32 * MAX_MIN_VOLT_LSB,
+            max_current: curr[0] as i8 as f32 * MAX_MIN_CURR_LSB / r_sense,
+            min_current: curr[1] as i8 as f32 * MAX_MIN_CURR_LSB / r_sense,
+            fault_flags: fault_log,
+        }
+    }
+}
+
+const MAX_MIN_VOLT_LSB: f32 = 0.02; // V
+const MAX_MIN_CURR_LSB: f32 = 400.0; // µV, divided by r_sense in mΩ gives mA
+
+/// Pack manufacture date, packed in nManfctrDate as
+/// (year - 1980) * 512 + month * 32 + day
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ManufactureDate {
+    /// Year, 1980 to 2107
+    pub year: u16,
+    /// Month, 1 to 12
+    pub month: u8,
+    /// Day of month, 1 to 31
+    pub day: u8,
+}
+
+impl ManufactureDate {
+    /// Decode the nManfctrDate register
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            year: 1980 + (bits >> 9),
+            month: ((bits >> 5) & 0x0F) as u8,
+            day: (bits & 0x1F) as u8,
+        }
+    }
+
+    /// Encode as the nManfctrDate register, or None if a field is out of range
+    pub fn to_bits(self) -> Option<u16> {
+        if !(1980..=2107).contains(&self.year)
+            || !(1..=12).contains(&self.month)
+            || !(1..=31).contains(&self.day)
+        {
+            return None;
+        }
+        Some((self.year - 1980) << 9 | (self.month as u16) << 5 | self.day as u16)
+    }
+}
+
+/// Pack identification fields programmed at manufacture
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ManufacturerInfo {
+    /// Manufacturer name, raw words of nManfctrName0-2
+    pub name: [u16; 3],
+    /// Manufacture date (nManfctrDate)
+    pub date: ManufactureDate,
+    /// Serial number, raw words of nSerialNumber0-3
+    pub serial_number: [u16; 4],
+}
+
+/// Fuel gauge status flags in the FStat register
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FStatFlags {
+    /// DNR: the first measurements after reset are not complete yet
+    pub data_not_ready: bool,
+    /// RelDt2: the cell has been relaxed for more than 48 to 96 minutes
+    pub long_relaxation: bool,
+    /// FQ: the end of charge was qualified as full
+    pub full_qualified: bool,
+    /// EDet: the cell has reached the empty voltage (VEmpty)
+    pub empty_detection: bool,
+    /// RelDt: the cell is relaxed and OCV readings are reliable
+    pub relaxed: bool,
+}
+
+impl FStatFlags {
+    /// Decode the FStat register
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            data_not_ready: bits & 1 != 0,
+            long_relaxation: bits & (1 << 6) != 0,
+            full_qualified: bits & (1 << 7) != 0,
+            empty_detection: bits & (1 << 8) != 0,
+            relaxed: bits & (1 << 9) != 0,
+        }
+    }
+
+    /// Encode as FStat register bits
+    pub fn to_bits(self) -> u16 {
+        self.data_not_ready as u16
+            | (self.long_relaxation as u16) << 6
+            | (self.full_qualified as u16) << 7
+            | (self.empty_detection as u16) << 8
+            | (self.relaxed as u16) << 9
+    }
+}
+
+/// Actual protection FET gate drive alongside the software overrides
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FetDriveStatus {
+    /// CHG FET gate is being driven on
+    pub charge_on: bool,
+    /// DIS FET gate is being driven on
+    pub discharge_on: bool,
+    /// CommStat.CHGOff is set
+    pub charge_commanded_off: bool,
+    /// CommStat.DISOff is set
+    pub discharge_commanded_off: bool,
+}
+
+impl FetDriveStatus {
+    /// CHG FET is off although software has not commanded it off, i.e. a
+    /// protection fault is holding it off
+    pub fn charge_held_off_by_protection(&self) -> bool {
+        !self.charge_on && !self.charge_commanded_off
+    }
+
+    /// DIS FET is off although software has not commanded it off, i.e. a
+    /// protection fault is holding it off
+    pub fn discharge_held_off_by_protection(&self) -> bool {
+        !self.discharge_on && !self.discharge_commanded_off
+    }
+}
+
+/// Agreement between the coulomb counting and voltage based SOC estimates
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GaugeHealth {
+    /// MixSOC (%)
+    pub mix_soc: f32,
+    /// VFSOC, the voltage fuel gauge's SOC (%)
+    pub vf_soc: f32,
+    /// |MixSOC - VFSOC| (percentage points)
+    pub divergence: f32,
+    /// The divergence exceeds GAUGE_DIVERGENCE_LIMIT and the gauge should be
+    /// recalibrated, e.g. by a full charge or a rest at a known state
+    pub needs_recalibration: bool,
+}
+
+/// Plausibility of the current sense measurement. The MAX17320 has no status
+/// bit for a failed sense resistor; an open shunt drives the ADC to full scale,
+/// which is what this detects. A shorted shunt reads as zero current and looks
+/// like a pack at rest, so combine this with GaugeHealth to catch slow drift.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CurrentSenseHealth {
+    /// Current (mA)
+    pub current_ma: f32,
+    /// AvgCurrent (mA)
+    pub avg_current_ma: f32,
+    /// Current or AvgCurrent is pinned at the ADC's full scale
+    pub saturated: bool,
+}
+
+impl CurrentSenseHealth {
+    /// Whether coulomb counting can be trusted
+    pub fn is_trusted(&self) -> bool {
+        !self.saturated
+    }
+}
+
+/// A register value decoded into its physical unit, see read_named_with_raw
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PhysicalValue {
+    /// Volts
+    Voltage(f32),
+    /// Milliamps, positive when charging
+    Current(f32),
+    /// Degrees Celsius
+    Temperature(f32),
+    /// Percent
+    Percentage(f32),
+    /// Seconds
+    Time(f32),
+    /// Milliamp hours
+    Capacity(f32),
+    /// No physical interpretation, e.g. status and configuration registers
+    Raw(u16),
+}
+
+/// Pending alerts with the readings that caused them, see poll_alerts. Each
+/// reading is only present if one of its alert bits was set.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlertReport {
+    /// Alert bits that were set in Status (StatusCode layout, POR excluded)
+    pub status: u16,
+    /// ProtAlrt, if Status.PA was set
+    pub protection_alert: Option<u16>,
+    /// VCell (V), if Vmn or Vmx was set
+    pub cell_voltage_v: Option<f32>,
+    /// Temp (°C), if Tmn or Tmx was set
+    pub temperature_c: Option<f32>,
+    /// RepSOC (%), if Smn, Smx or dSOCi was set
+    pub state_of_charge: Option<f32>,
+    /// Current (mA), if Imn or Imx was set
+    pub current_ma: Option<f32>,
+}
+
+/// Summary of the alert setup, see read_alert_configuration. Threshold pairs
+/// are (min, max).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlertConfig {
+    /// Threshold violations drive the ALRT pin (Config.Aen)
+    pub output_enabled: bool,
+    /// The ALRT pin is active high (Config.ALRTp)
+    pub active_high: bool,
+    /// An alert puts the device into shutdown (nConfig.ALSH)
+    pub shutdown_on_alert: bool,
+    /// Voltage alerts stay set until cleared by software (Config.VS)
+    pub voltage_sticky: bool,
+    /// Temperature alerts stay set until cleared by software (Config.TS)
+    pub temperature_sticky: bool,
+    /// SOC alerts stay set until cleared by software (Config.SS)
+    pub soc_sticky: bool,
+    /// Temperature alerts are enabled (Config2.TAlrtEn)
+    pub temperature_alert_enabled: bool,
+    /// An alert is raised on every 1% SOC change (Config2.dSOCen)
+    pub soc_change_alert_enabled: bool,
+    /// Cell voltage thresholds (V)
+    pub voltage_threshold_v: (f32, f32),
+    /// Temperature thresholds (°C)
+    pub temperature_threshold_c: (i8, i8),
+    /// SOC thresholds (%)
+    pub soc_threshold_percent: (u8, u8),
+    /// Current thresholds (mA)
+    pub current_threshold_ma: (f32, f32),
+}
+
+/// Capacities behind a capacity overflow fault, see read_capacity_overflow_detail
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CapacityOverflowDetail {
+    /// Net charge accumulated by the coulomb counter (QH, mAh)
+    pub accumulated_mah: f32,
+    /// Full capacity the gauge is working to (FullCapRep, mAh)
+    pub full_mah: f32,
+    /// Configured design capacity (nDesignCap, mAh)
+    pub design_mah: f32,
+}
+
+impl CapacityOverflowDetail {
+    /// How far the accumulated charge is above the design capacity (mAh)
+    pub fn overflow_mah(&self) -> f32 {
+        self.accumulated_mah - self.design_mah
+    }
+}
+
+/// Voltage across the protection FETs, see read_fet_voltage_drop
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FetVoltageDrop {
+    /// BATT - PCKP (V)
+    pub drop_v: f32,
+    /// Current at the time of the measurement (mA)
+    pub current_ma: f32,
+    /// Effective series resistance of both FETs (mΩ), None if too little
+    /// current is flowing for a meaningful estimate
+    pub resistance_mohm: Option<f32>,
+}
+
+/// Which FET body diode is carrying current, see read_body_diode_conduction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BodyDiodeConduction {
+    /// No FET is conducting through its body diode
+    None,
+    /// Charge current flows through the body diode of the discharge FET, which
+    /// is off
+    DischargeFet,
+    /// Discharge current flows through the body diode of the charge FET, which
+    /// is off
+    ChargeFet,
+}
+
+/// What is attached to the pack terminals, see read_pack_connection_state
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PackConnection {
+    /// A load is drawing current from the pack
+    Connected,
+    /// Nothing is drawing or supplying current. An attached but idle host looks
+    /// the same, since the gauge sits inside the pack.
+    Disconnected,
+    /// A charger is supplying current, or is raising PACK+ above BATT while the
+    /// charge FET is off
+    ChargerPresent,
+}
+
+/// Go/no-go summary of the pack, see is_pack_healthy
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PackHealth {
+    /// No faults and all checks pass
+    Ok,
+    /// Usable, but a measurement or estimate cannot be fully trusted
+    Degraded(DegradedReason),
+    /// The protector is holding a FET off for this fault (the first active one
+    /// in ProtStatus bit order)
+    Fault(crate::ProtStatusCode),
+    /// A permanent failure has been latched; the argument is the nBattStatus
+    /// latch using the ProtAlertCode bit layout
+    PermanentFail(u16),
+}
+
+/// Why a pack was reported as PackHealth::Degraded
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DegradedReason {
+    /// The current measurement is pinned at full scale, see CurrentSenseHealth
+    CurrentSenseUntrusted,
+    /// MixSOC and VFSOC have drifted apart, see GaugeHealth
+    GaugeNeedsRecalibration,
+}
+
+/// One set of core measurements tagged with the gauge's Timer register
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sample {
+    /// Raw Timer register, 175.8ms per LSB. Two samples with the same value were
+    /// taken within the same gauge update and hold the same data.
+    pub timer: u16,
+    /// Pack voltage, Batt (V)
+    pub pack_voltage: f32,
+    /// Battery current (mA), positive when charging
+    pub current_ma: f32,
+    /// Temperature (°C)
+    pub temperature: f32,
+    /// Reported state of charge (%)
+    pub state_of_charge: f32,
+}
+
+impl Sample {
+    /// Pack voltage change counted as movement (V), about three Batt LSBs
+    pub const VOLTAGE_DEADBAND_V: f32 = 0.001;
+    /// Current change counted as movement (mA)
+    pub const CURRENT_DEADBAND_MA: f32 = 1.0;
+    /// Temperature change counted as movement (°C)
+    pub const TEMPERATURE_DEADBAND_C: f32 = 0.1;
+    /// State of charge change counted as movement (%)
+    pub const SOC_DEADBAND_PERCENT: f32 = 0.01;
+
+    /// Whether this sample moved since previous: the Timer advanced, or a
+    /// measurement changed by more than its deadband
+    pub fn has_changed_from(&self, previous: &Sample) -> bool {
+        let moved = |now: f32, before: f32, deadband: f32| (now - before).abs() > deadband;
+        self.timer != previous.timer
+            || moved(
+                self.pack_voltage,
+                previous.pack_voltage,
+                Self::VOLTAGE_DEADBAND_V,
+            )
+            || moved(
+                self.current_ma,
+                previous.current_ma,
+                Self::CURRENT_DEADBAND_MA,
+            )
+            || moved(
+                self.temperature,
+                previous.temperature,
+                Self::TEMPERATURE_DEADBAND_C,
+            )
+            || moved(
+                self.state_of_charge,
+                previous.state_of_charge,
+                Self::SOC_DEADBAND_PERCENT,
+            )
+    }
+}
+
+/// Nominal pack ratings derived from the configuration, see read_pack_specifications
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PackSpec {
+    /// Cells in series (nPackCfg)
+    pub series_cells: u8,
+    /// Nominal pack voltage (V)
+    pub nominal_voltage: f32,
+    /// Design capacity of the pack (mAh), which covers any parallel cells
+    pub capacity_mah: f32,
+    /// Nominal energy (Wh)
+    pub energy_wh: f32,
+}
+
+/// Per cell voltages that bound where the gauge's readings are meaningful, see
+/// read_operating_voltage_limits
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OperatingVoltageLimits {
+    /// Voltage the gauge treats as empty, where RepSOC and RepCap reach 0 (VEmpty.VE)
+    pub empty_v: f32,
+    /// Voltage the cell must recover to after reaching empty before the gauge
+    /// leaves its empty state (VEmpty.VR)
+    pub recovery_v: f32,
+    /// Undervoltage protection trip, below which the protector turns the
+    /// discharge FET off (nUVPrtTh)
+    pub lockout_v: f32,
+}
+
+/// Gauge state recorded by the driver when full detection last triggered, see
+/// read_last_full_charge_info
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FullChargeInfo {
+    /// Charge cycle count at the time (Cycles)
+    pub cycles: f32,
+    /// Gauge operating time at the time, comparable with read_operating_time
+    pub operating_time: core::time::Duration,
+    /// Full capacity learned at the time (FullCapRep, mAh)
+    pub full_capacity_mah: f32,
+}
+
+/// Energy throughput integrated by the driver, see read_cumulative_energy
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct CumulativeEnergy {
+    /// Energy charged into the pack (Wh)
+    pub charged_wh: f32,
+    /// Energy discharged from the pack (Wh)
+    pub discharged_wh: f32,
+}
+
+/// Why the discharge FET is off, see read_discharge_disabled_reason
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DischargeDisabledReason {
+    /// The discharge FET is on
+    None,
+    /// Software turned it off with CommStat.DISOff
+    UserCommanded,
+    /// Undervoltage protection
+    Undervoltage,
+    /// Overdischarge current protection
+    Overcurrent,
+    /// Discharge or die overtemperature protection
+    Overtemperature,
+    /// Another protection fault, such as ship mode or a permanent failure;
+    /// check read_protection_status
+    OtherProtection,
+}
+
+/// How close the protector is to clearing its active faults, see read_recovery_status
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RecoveryStatus {
+    /// Active faults, using the ProtStatusCode bit layout
+    pub active_faults: u16,
+    /// Undervoltage recovery progress, None if undervoltage is not tripped
+    pub undervoltage: Option<VoltageRecovery>,
+}
+
+/// Progress of a cell voltage towards the level that clears a voltage fault
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VoltageRecovery {
+    /// The deciding cell voltage, e.g. the lowest cell for undervoltage (V)
+    pub cell_v: f32,
+    /// Voltage the cell must reach for the fault to clear (V)
+    pub recovery_v: f32,
+    /// The recovery condition is currently met and the FET is about to re-enable
+    pub met: bool,
+}